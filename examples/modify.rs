@@ -1,7 +1,7 @@
 extern crate hyper;
 extern crate pocket;
 
-use pocket::{send::PocketSendAction, send::PocketSendRequest, Pocket};
+use pocket::{send::PocketSendAction, send::SendBatch, send::Tags, Pocket};
 use std::error::Error;
 use url::Url;
 
@@ -13,42 +13,29 @@ async fn main() -> Result<(), Box<dyn Error>> {
     );
     let item_id = std::env::var("POCKET_ITEM_ID")?.parse::<u64>()?;
 
-    let results = pocket
-        .send(&PocketSendRequest {
-            actions: &[
-                &PocketSendAction::Add {
-                    item_id: None,
-                    ref_id: None,
-                    tags: Some("example-tag".to_string()),
-                    time: None,
-                    title: Some("Example title".to_string()),
-                    url: Some(Url::parse("https://example.com")?),
-                },
-                &PocketSendAction::Archive {
-                    item_id,
-                    time: None,
-                },
-                &PocketSendAction::TagsAdd {
-                    item_id,
-                    tags: "one,two".to_string(),
-                    time: None,
-                },
-                &PocketSendAction::TagRename {
-                    old_tag: "one".to_string(),
-                    new_tag: "1".to_string(),
-                    time: None,
-                },
-                &PocketSendAction::Add {
-                    item_id: None,
-                    ref_id: None,
-                    tags: None,
-                    time: None,
-                    title: None,
-                    url: None,
-                },
-            ],
+    let mut batch = SendBatch::new();
+    batch
+        .push(PocketSendAction::Add {
+            item_id: None,
+            ref_id: None,
+            tags: Some(Tags::new(["example-tag"])?),
+            time: None,
+            title: Some("Example title".to_string()),
+            url: Some(Url::parse("https://example.com")?),
         })
-        .await?;
+        .archive(item_id)
+        .tags_add(item_id, &["one", "two"])?
+        .tag_rename("one", "1")?
+        .push(PocketSendAction::Add {
+            item_id: None,
+            ref_id: None,
+            tags: None,
+            time: None,
+            title: None,
+            url: None,
+        });
+
+    let results = pocket.send_batch(&batch).await?;
     println!("results: {:?}", results);
     Ok(())
 }
@@ -1,3 +1,4 @@
+use crate::schema::ExtraFields;
 use crate::serialization::*;
 use crate::{ItemAuthor, ItemVideo, PocketImage, PocketItemHas};
 use chrono::{DateTime, Utc};
@@ -16,7 +17,7 @@ pub struct PocketAddRequest<'a> {
 }
 
 impl<'a> PocketAddRequest<'a> {
-    pub fn new(url: &Url) -> PocketAddRequest {
+    pub fn new(url: &Url) -> PocketAddRequest<'_> {
         PocketAddRequest {
             url,
             title: None,
@@ -117,19 +118,27 @@ pub struct PocketAddedItem {
 
     #[serde(with = "url_serde")]
     pub given_url: Url,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
 }
 
 #[derive(Deserialize, Debug, PartialEq)]
 pub struct PocketAddResponse {
     pub item: PocketAddedItem,
     pub status: u16,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::utils::remove_whitespace;
-    use chrono::TimeZone;
+    use chrono::NaiveDateTime;
 
     // PocketAddRequest
     #[test]
@@ -180,7 +189,7 @@ mod test {
                   mime_type: "text/html".parse().ok(),
                   content_length: 648,
                   encoding: "utf-8".to_string(),
-                  date_resolved: Utc.datetime_from_str("2020-03-03 12:20:37", FORMAT).ok(),
+                  date_resolved: NaiveDateTime::parse_from_str("2020-03-03 12:20:37", FORMAT).ok().map(|d| d.and_utc()),
                   date_published: None,
                   title: "Example Domain".to_string(),
                   excerpt: "This domain is for use in illustrative examples in documents. You may use this domain in literature without prior coordination or asking for permission. More information...".to_string(),
@@ -199,8 +208,10 @@ mod test {
                   videos: Some(vec![]),
                   resolved_normal_url: Url::parse("http://example.com").ok(),
                   given_url: Url::parse("https://example.com").unwrap(),
+                  extra: Default::default(),
               },
               status: 1,
+              extra: Default::default(),
           };
         let response = r#"
               {
@@ -240,7 +251,7 @@ mod test {
               }
          "#;
 
-        let actual: PocketAddResponse = serde_json::from_str(&response).unwrap();
+        let actual: PocketAddResponse = serde_json::from_str(response).unwrap();
 
         assert_eq!(actual, expected);
     }
@@ -279,8 +290,10 @@ mod test {
                 videos: None,
                 resolved_normal_url: None,
                 given_url: Url::parse("https://dc7ad3b2-942e-41c5-9154-a1b545752102.com").unwrap(),
+                extra: Default::default(),
             },
             status: 1,
+            extra: Default::default(),
         };
         let response = r#"
               {
@@ -316,7 +329,7 @@ mod test {
               }
          "#;
 
-        let actual: PocketAddResponse = serde_json::from_str(&response).unwrap();
+        let actual: PocketAddResponse = serde_json::from_str(response).unwrap();
 
         assert_eq!(actual, expected);
     }
@@ -1,8 +1,15 @@
-use crate::client::PocketClient;
+use crate::client::{CallOptions, ClientOptions, PocketClient};
 use crate::errors::PocketError;
+use crate::retry::RetryPolicy;
+use crate::transport::Transport;
+use crate::utils::with_trailing_slash;
 use crate::Pocket;
 use crate::PocketResult;
+use crate::DEFAULT_BASE_URL;
+use hyper::HeaderMap;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
 use url::Url;
 
 #[derive(Serialize)]
@@ -31,19 +38,23 @@ pub struct PocketAuthorizeResponse {
     state: Option<String>,
 }
 
+pub const DEFAULT_AUTH_URL: &str = "https://getpocket.com/auth/authorize";
+
 pub struct PocketAuthentication {
     consumer_key: String,
     redirect_uri: String,
+    base_url: Url,
+    auth_url: Url,
     client: PocketClient,
 }
 
 impl PocketAuthentication {
     pub fn new(consumer_key: &str, redirect_uri: &str) -> PocketAuthentication {
-        PocketAuthentication {
-            consumer_key: consumer_key.to_string(),
-            redirect_uri: redirect_uri.to_string(),
-            client: PocketClient::new(),
-        }
+        PocketAuthenticationBuilder::new(consumer_key, redirect_uri).build()
+    }
+
+    pub fn builder(consumer_key: &str, redirect_uri: &str) -> PocketAuthenticationBuilder {
+        PocketAuthenticationBuilder::new(consumer_key, redirect_uri)
     }
 
     pub async fn request(&self, state: Option<&str>) -> PocketResult<String> {
@@ -53,8 +64,9 @@ impl PocketAuthentication {
             state,
         };
 
+        let url = self.base_url.join("oauth/request")?;
         self.client
-            .post("https://getpocket.com/v3/oauth/request", &body)
+            .post(url.as_str(), &body, CallOptions::default())
             .await
             .and_then(|r: PocketOAuthResponse| {
                 PocketAuthentication::verify_state(state, r.state.as_deref()).map(|()| r.code)
@@ -65,7 +77,12 @@ impl PocketAuthentication {
         match (request_state, response_state) {
             (Some(s1), Some(s2)) if s1 == s2 => Ok(()),
             (None, None) => Ok(()),
-            _ => Err(PocketError::Proto(0, "State does not match".to_string())),
+            _ => Err(PocketError::Proto {
+                code: 0,
+                message: "State does not match".to_string(),
+                status: None,
+                headers: HeaderMap::new(),
+            }),
         }
     }
 
@@ -74,8 +91,8 @@ impl PocketAuthentication {
             ("request_token", code),
             ("redirect_uri", &self.redirect_uri),
         ];
-        let mut url = Url::parse("https://getpocket.com/auth/authorize").unwrap();
-        url.query_pairs_mut().extend_pairs(params.into_iter());
+        let mut url = self.auth_url.clone();
+        url.query_pairs_mut().extend_pairs(params);
         url
     }
 
@@ -85,8 +102,9 @@ impl PocketAuthentication {
             code,
         };
 
+        let url = self.base_url.join("oauth/authorize")?;
         self.client
-            .post("https://getpocket.com/v3/oauth/authorize", &body)
+            .post(url.as_str(), &body, CallOptions::default())
             .await
             .and_then(|r: PocketAuthorizeResponse| {
                 PocketAuthentication::verify_state(state, r.state.as_deref()).map(|()| PocketUser {
@@ -98,6 +116,80 @@ impl PocketAuthentication {
     }
 }
 
+pub struct PocketAuthenticationBuilder {
+    consumer_key: String,
+    redirect_uri: String,
+    base_url: Url,
+    auth_url: Url,
+    options: ClientOptions,
+}
+
+impl PocketAuthenticationBuilder {
+    pub fn new(consumer_key: &str, redirect_uri: &str) -> PocketAuthenticationBuilder {
+        PocketAuthenticationBuilder {
+            consumer_key: consumer_key.to_string(),
+            redirect_uri: redirect_uri.to_string(),
+            base_url: Url::parse(DEFAULT_BASE_URL).unwrap(),
+            auth_url: Url::parse(DEFAULT_AUTH_URL).unwrap(),
+            options: ClientOptions::default(),
+        }
+    }
+
+    /// Sets the URL the `oauth/request` and `oauth/authorize` methods are resolved against.
+    pub fn base_url(&mut self, base_url: Url) -> &mut PocketAuthenticationBuilder {
+        self.base_url = with_trailing_slash(base_url);
+        self
+    }
+
+    /// Sets the page users are sent to by `authorize_url`.
+    pub fn auth_url(&mut self, auth_url: Url) -> &mut PocketAuthenticationBuilder {
+        self.auth_url = auth_url;
+        self
+    }
+
+    pub fn retry(&mut self, retry: RetryPolicy) -> &mut PocketAuthenticationBuilder {
+        self.options.retry = retry;
+        self
+    }
+
+    /// Sends requests through `transport` instead of the default `HyperTransport`.
+    pub fn transport<T: Transport + 'static>(
+        &mut self,
+        transport: T,
+    ) -> &mut PocketAuthenticationBuilder {
+        self.options.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Timeout for establishing a connection. Only applies to the default transport.
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut PocketAuthenticationBuilder {
+        self.options.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for waiting on the response headers and on every chunk of the body.
+    pub fn read_timeout(&mut self, timeout: Duration) -> &mut PocketAuthenticationBuilder {
+        self.options.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for a whole request attempt, from sending it to reading the full body.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut PocketAuthenticationBuilder {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    pub fn build(&self) -> PocketAuthentication {
+        PocketAuthentication {
+            consumer_key: self.consumer_key.clone(),
+            redirect_uri: self.redirect_uri.clone(),
+            base_url: self.base_url.clone(),
+            auth_url: self.auth_url.clone(),
+            client: PocketClient::new(self.options.clone()),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct PocketUser {
     pub consumer_key: String,
@@ -116,6 +208,34 @@ impl PocketUser {
 mod test {
     use super::*;
     use crate::utils::remove_whitespace;
+    use crate::utils::test_server::{TestResponse, TestServer};
+
+    #[test]
+    fn test_authorize_url_uses_auth_url() {
+        let auth = PocketAuthentication::builder("consumer_key", "http://localhost/finish")
+            .auth_url(Url::parse("http://localhost:8080/auth/authorize").unwrap())
+            .build();
+
+        let url = auth.authorize_url("code");
+
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:8080/auth/authorize?request_token=code&redirect_uri=http%3A%2F%2Flocalhost%2Ffinish"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_uses_base_url() {
+        let server = TestServer::start(vec![TestResponse::json(r#"{"code":"code","state":null}"#)]);
+        let auth = PocketAuthentication::builder("consumer_key", "http://localhost/finish")
+            .base_url(server.url.clone())
+            .build();
+
+        let code = auth.request(None).await.unwrap();
+
+        assert_eq!(code, "code");
+        assert_eq!(server.requests()[0].uri, "/v3/oauth/request");
+    }
 
     #[test]
     fn test_serialize_auth_request() {
@@ -0,0 +1,265 @@
+//! Synchronous wrappers around `Pocket` and `PocketAuthentication`.
+//!
+//! Each wrapper owns a single-threaded tokio runtime and blocks the calling
+//! thread until the request completes, so it must not be used from within an
+//! async context.
+
+use crate::add::{PocketAddRequest, PocketAddedItem};
+use crate::auth::PocketUser;
+use crate::filter::ItemFilter;
+use crate::get::{PocketGetLenientResponse, PocketGetRequest, PocketGetResponse, PocketItem};
+use crate::rate_limit::RateLimitStatus;
+use crate::send::{PocketSendRequest, PocketSendResponse, SendBatch, Tags};
+use crate::sync::{SyncDelta, SyncState};
+use crate::tag_query::TagQuery;
+use crate::PocketResult;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use url::Url;
+
+struct Runtime(Mutex<tokio::runtime::Runtime>);
+
+impl Runtime {
+    fn new() -> PocketResult<Runtime> {
+        tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .map(|runtime| Runtime(Mutex::new(runtime)))
+            .map_err(From::from)
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.0.lock().unwrap().block_on(future)
+    }
+}
+
+pub struct Pocket {
+    inner: crate::Pocket,
+    runtime: Runtime,
+}
+
+impl Pocket {
+    pub fn new(consumer_key: &str, access_token: &str) -> PocketResult<Pocket> {
+        Pocket::from_async(crate::Pocket::new(consumer_key, access_token))
+    }
+
+    /// Wraps an async `Pocket`, e.g. one configured with `Pocket::builder`.
+    pub fn from_async(inner: crate::Pocket) -> PocketResult<Pocket> {
+        Ok(Pocket {
+            inner,
+            runtime: Runtime::new()?,
+        })
+    }
+
+    #[inline]
+    pub fn access_token(&self) -> &str {
+        self.inner.access_token()
+    }
+
+    pub fn last_rate_limit(&self) -> Option<RateLimitStatus> {
+        self.inner.last_rate_limit()
+    }
+
+    pub fn add(&self, request: &PocketAddRequest<'_>) -> PocketResult<PocketAddedItem> {
+        self.runtime.block_on(self.inner.add(request))
+    }
+
+    pub fn get(&self, request: &PocketGetRequest<'_>) -> PocketResult<Vec<PocketItem>> {
+        self.runtime.block_on(self.inner.get(request))
+    }
+
+    pub fn get_with_timeout(
+        &self,
+        request: &PocketGetRequest<'_>,
+        timeout: Duration,
+    ) -> PocketResult<Vec<PocketItem>> {
+        self.runtime
+            .block_on(self.inner.get_with_timeout(request, timeout))
+    }
+
+    pub fn get_lenient(
+        &self,
+        request: &PocketGetRequest<'_>,
+    ) -> PocketResult<PocketGetLenientResponse> {
+        self.runtime.block_on(self.inner.get_lenient(request))
+    }
+
+    pub fn get_filtered(
+        &self,
+        request: &PocketGetRequest<'_>,
+        filter: &ItemFilter,
+    ) -> PocketResult<Vec<PocketItem>> {
+        self.runtime
+            .block_on(self.inner.get_filtered(request, filter))
+    }
+
+    pub fn get_tagged(
+        &self,
+        request: &PocketGetRequest<'_>,
+        query: &TagQuery,
+    ) -> PocketResult<Vec<PocketItem>> {
+        self.runtime.block_on(self.inner.get_tagged(request, query))
+    }
+
+    pub fn get_response(&self, request: &PocketGetRequest<'_>) -> PocketResult<PocketGetResponse> {
+        self.runtime.block_on(self.inner.get_response(request))
+    }
+
+    pub fn sync(&self, state: &mut SyncState) -> PocketResult<SyncDelta> {
+        self.runtime.block_on(self.inner.sync(state))
+    }
+
+    pub fn send(&self, request: &PocketSendRequest<'_>) -> PocketResult<PocketSendResponse> {
+        self.runtime.block_on(self.inner.send(request))
+    }
+
+    pub fn send_batch(&self, batch: &SendBatch) -> PocketResult<PocketSendResponse> {
+        self.runtime.block_on(self.inner.send_batch(batch))
+    }
+
+    pub fn archive(&self, item_id: u64) -> PocketResult<()> {
+        self.runtime.block_on(self.inner.archive(item_id))
+    }
+
+    pub fn readd(&self, item_id: u64) -> PocketResult<()> {
+        self.runtime.block_on(self.inner.readd(item_id))
+    }
+
+    pub fn favorite(&self, item_id: u64) -> PocketResult<()> {
+        self.runtime.block_on(self.inner.favorite(item_id))
+    }
+
+    pub fn unfavorite(&self, item_id: u64) -> PocketResult<()> {
+        self.runtime.block_on(self.inner.unfavorite(item_id))
+    }
+
+    pub fn delete(&self, item_id: u64) -> PocketResult<()> {
+        self.runtime.block_on(self.inner.delete(item_id))
+    }
+
+    pub fn add_tags(&self, item_id: u64, tags: Tags) -> PocketResult<()> {
+        self.runtime.block_on(self.inner.add_tags(item_id, tags))
+    }
+
+    pub fn remove_tags(&self, item_id: u64, tags: Tags) -> PocketResult<()> {
+        self.runtime.block_on(self.inner.remove_tags(item_id, tags))
+    }
+
+    pub fn replace_tags(&self, item_id: u64, tags: Tags) -> PocketResult<()> {
+        self.runtime
+            .block_on(self.inner.replace_tags(item_id, tags))
+    }
+
+    pub fn clear_tags(&self, item_id: u64) -> PocketResult<()> {
+        self.runtime.block_on(self.inner.clear_tags(item_id))
+    }
+
+    pub fn rename_tag(&self, old_tag: &str, new_tag: &str) -> PocketResult<()> {
+        self.runtime
+            .block_on(self.inner.rename_tag(old_tag, new_tag))
+    }
+
+    pub fn delete_tag(&self, tag: &str) -> PocketResult<()> {
+        self.runtime.block_on(self.inner.delete_tag(tag))
+    }
+
+    pub fn filter(&self) -> PocketGetRequest<'_> {
+        self.inner.filter()
+    }
+}
+
+pub struct PocketAuthentication {
+    inner: crate::auth::PocketAuthentication,
+    runtime: Runtime,
+}
+
+impl PocketAuthentication {
+    pub fn new(consumer_key: &str, redirect_uri: &str) -> PocketResult<PocketAuthentication> {
+        PocketAuthentication::from_async(crate::auth::PocketAuthentication::new(
+            consumer_key,
+            redirect_uri,
+        ))
+    }
+
+    /// Wraps an async `PocketAuthentication`, e.g. one configured with
+    /// `PocketAuthentication::builder`.
+    pub fn from_async(
+        inner: crate::auth::PocketAuthentication,
+    ) -> PocketResult<PocketAuthentication> {
+        Ok(PocketAuthentication {
+            inner,
+            runtime: Runtime::new()?,
+        })
+    }
+
+    pub fn request(&self, state: Option<&str>) -> PocketResult<String> {
+        self.runtime.block_on(self.inner.request(state))
+    }
+
+    pub fn authorize_url(&self, code: &str) -> Url {
+        self.inner.authorize_url(code)
+    }
+
+    pub fn authorize(&self, code: &str, state: Option<&str>) -> PocketResult<PocketUser> {
+        self.runtime.block_on(self.inner.authorize(code, state))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::test_server::{TestResponse, TestServer};
+    use std::sync::mpsc;
+    use std::thread;
+
+    fn start_server(responses: Vec<TestResponse>) -> TestServer {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut runtime = tokio::runtime::Builder::new()
+                .basic_scheduler()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(async move {
+                tx.send(TestServer::start(responses)).unwrap();
+                futures::future::pending::<()>().await
+            });
+        });
+        rx.recv().unwrap()
+    }
+
+    #[test]
+    fn test_blocking_get() {
+        let server = start_server(vec![TestResponse::json(
+            r#"{"status":1,"complete":1,"list":[],"error":null,"search_meta":{"search_type":"normal"},"since":1584221353}"#,
+        )]);
+        let pocket = Pocket::from_async(
+            crate::Pocket::builder("consumer_key", "access_token")
+                .base_url(server.url.clone())
+                .build(),
+        )
+        .unwrap();
+
+        let items = pocket.get(&PocketGetRequest::new()).unwrap();
+
+        assert!(items.is_empty());
+        assert_eq!(server.requests()[0].uri, "/v3/get");
+    }
+
+    #[test]
+    fn test_blocking_request() {
+        let server = start_server(vec![TestResponse::json(
+            r#"{"code":"code","state":"state"}"#,
+        )]);
+        let auth = PocketAuthentication::from_async(
+            crate::auth::PocketAuthentication::builder("consumer_key", "rustapi:finishauth")
+                .base_url(server.url.clone())
+                .build(),
+        )
+        .unwrap();
+
+        assert_eq!(auth.request(Some("state")).unwrap(), "code");
+    }
+}
@@ -1,41 +1,84 @@
-use crate::errors::PocketError;
+use crate::errors::{PocketError, TimeoutKind, MAX_ERROR_BODY_LEN};
 use crate::headers::{HEADER_XACCEPT, HEADER_XERROR, HEADER_XERROR_CODE};
+use crate::rate_limit::{RateLimitStatus, TokenBucket, DEFAULT_MAX_THROTTLE_DELAY};
+use crate::retry::{retry_after, RetryPolicy};
+use crate::transport::{HyperTransport, Transport};
 use crate::PocketResult;
-use bytes::buf::BufExt as _;
-use futures::TryFutureExt;
-use hyper::client::{Client, HttpConnector};
+use bytes::{Bytes, BytesMut};
+use hyper::body::HttpBody as _;
 use hyper::Body;
+use hyper::HeaderMap;
 use hyper::Method;
 use hyper::Request;
+use hyper::Response;
+use hyper::StatusCode;
 use hyper::Uri;
-use hyper_tls::HttpsConnector;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::convert::{From, TryFrom};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::{delay_for, delay_until};
+
+#[derive(Default, Clone)]
+pub struct ClientOptions {
+    pub min_remaining: Option<u32>,
+    /// Defaults to `DEFAULT_MAX_THROTTLE_DELAY`.
+    pub max_throttle_delay: Option<Duration>,
+    pub rate_limiter: Option<Arc<TokenBucket>>,
+    pub retry: RetryPolicy,
+    pub transport: Option<Arc<dyn Transport>>,
+    pub connect_timeout: Option<Duration>,
+    pub read_timeout: Option<Duration>,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct CallOptions {
+    pub idempotent: bool,
+    pub timeout: Option<Duration>,
+}
 
 pub struct PocketClient {
-    client: Client<HttpsConnector<HttpConnector>>,
+    transport: Arc<dyn Transport>,
+    options: ClientOptions,
+    rate_limit: Mutex<Option<RateLimitStatus>>,
 }
 
 impl PocketClient {
-    pub fn new() -> PocketClient {
-        let https = HttpsConnector::new();
-        let client = Client::builder().build::<_, hyper::Body>(https);
+    pub fn new(options: ClientOptions) -> PocketClient {
+        let transport = options.transport.clone().unwrap_or_else(|| {
+            Arc::new(HyperTransport::with_connect_timeout(
+                options.connect_timeout,
+            ))
+        });
 
-        PocketClient { client }
+        PocketClient {
+            transport,
+            options,
+            rate_limit: Mutex::new(None),
+        }
     }
 
-    pub async fn get<T, Resp>(&self, url: T) -> PocketResult<Resp>
+    pub fn last_rate_limit(&self) -> Option<RateLimitStatus> {
+        *self.rate_limit.lock().unwrap()
+    }
+
+    pub async fn get<T, Resp>(&self, url: T, call: CallOptions) -> PocketResult<Resp>
     where
         Uri: TryFrom<T>,
         <Uri as TryFrom<T>>::Error: Into<hyper::http::Error>,
         Resp: DeserializeOwned,
     {
-        let request = Request::builder().uri(url).body(Body::empty()).unwrap();
-        self.request(request).await
+        let request = Request::builder()
+            .uri(url)
+            .body(Bytes::new())
+            .map_err(|e| PocketError::InvalidUrl(e.to_string()))?;
+        self.request(request, call).await
     }
 
-    pub async fn post<T, B, Resp>(&self, url: T, body: &B) -> PocketResult<Resp>
+    pub async fn post<T, B, Resp>(&self, url: T, body: &B, call: CallOptions) -> PocketResult<Resp>
     where
         Uri: TryFrom<T>,
         <Uri as TryFrom<T>>::Error: Into<hyper::http::Error>,
@@ -43,38 +86,190 @@ impl PocketClient {
         Resp: DeserializeOwned,
     {
         let app_json = "application/json";
-        let body = serde_json::to_string(body).map(Body::from)?;
+        let body = serde_json::to_vec(body).map(Bytes::from)?;
         let request = Request::builder()
             .method(Method::POST)
             .uri(url)
             .header(hyper::header::CONTENT_TYPE, app_json)
             .header(HEADER_XACCEPT, app_json)
             .body(body)
-            .unwrap();
+            .map_err(|e| PocketError::InvalidUrl(e.to_string()))?;
+
+        self.request(request, call).await
+    }
+
+    async fn throttle(&self) {
+        if let Some(ref limiter) = self.options.rate_limiter {
+            limiter.acquire().await;
+        }
+
+        let throttled_until = self.options.min_remaining.and_then(|min_remaining| {
+            self.last_rate_limit()
+                .and_then(|status| status.throttled_until(min_remaining))
+        });
+        if let Some(instant) = throttled_until {
+            let max_delay = self
+                .options
+                .max_throttle_delay
+                .unwrap_or(DEFAULT_MAX_THROTTLE_DELAY);
+            match Instant::now().checked_add(max_delay) {
+                Some(latest) => delay_until(instant.min(latest).into()).await,
+                None => delay_until(instant.into()).await,
+            }
+        }
+    }
+
+    async fn request<Resp: DeserializeOwned>(
+        &self,
+        request: Request<Bytes>,
+        call: CallOptions,
+    ) -> PocketResult<Resp> {
+        let retry = &self.options.retry;
+        let mut attempt = 1;
 
-        self.request(request).await
+        loop {
+            self.throttle().await;
+
+            let result = self.exchange(&request, call).await;
+            if let Ok(ref r) = result {
+                self.record_rate_limit(r.headers());
+            }
+            let delay = match result {
+                Err(_) => Some(retry.backoff(attempt)),
+                Ok(ref r) if is_retryable_status(r.status()) => {
+                    Some(retry.delay(attempt, retry_after(r.headers())))
+                }
+                Ok(_) => None,
+            };
+
+            match delay {
+                Some(delay) if retry.allows_retry(attempt, call.idempotent) => {
+                    delay_for(delay).await;
+                    attempt += 1;
+                }
+                _ => return self.handle_response(result?),
+            }
+        }
     }
 
-    async fn request<Resp: DeserializeOwned>(&self, request: Request<Body>) -> PocketResult<Resp> {
-        self.client
-            .request(request)
-            .map_err(From::from)
-            .and_then(|r| async move {
-                match r.headers().get(HEADER_XERROR_CODE) {
-                    None => {
-                        let body = hyper::body::aggregate(r).await?;
-                        serde_json::from_reader(body.reader()).map_err(From::from)
-                    }
-                    Some(code) => Err(PocketError::Proto(
-                        code.to_str().unwrap().parse().unwrap(),
-                        r.headers()
-                            .get(HEADER_XERROR)
-                            .map(|v| v.to_str().unwrap())
-                            .unwrap_or("unknown protocol error")
-                            .to_string(),
-                    )),
+    /// Sends one attempt of `request` and reads the response body, bounded by the
+    /// read timeout and the call's (or the client's) total timeout. The body of an
+    /// error that `handle_response` reports as `PocketError::Status` is only read
+    /// up to `MAX_ERROR_BODY_LEN` bytes.
+    async fn exchange(
+        &self,
+        request: &Request<Bytes>,
+        call: CallOptions,
+    ) -> PocketResult<Response<Bytes>> {
+        let read_timeout = self.options.read_timeout;
+        let exchange = async {
+            let response = with_timeout(
+                read_timeout,
+                TimeoutKind::Read,
+                self.transport.send(copy_request(request)),
+            )
+            .await??;
+
+            let (parts, mut body) = response.into_parts();
+            let limit = if is_status_error(parts.status, &parts.headers) {
+                MAX_ERROR_BODY_LEN
+            } else {
+                usize::MAX
+            };
+            let mut bytes = BytesMut::new();
+            while bytes.len() < limit {
+                match with_timeout(read_timeout, TimeoutKind::Read, body.data()).await? {
+                    Some(chunk) => bytes.extend_from_slice(&chunk?),
+                    None => break,
                 }
-            })
+            }
+
+            Ok(Response::from_parts(parts, bytes.freeze()))
+        };
+
+        with_timeout(
+            call.timeout.or(self.options.timeout),
+            TimeoutKind::Total,
+            exchange,
+        )
+        .await?
+    }
+
+    fn record_rate_limit(&self, headers: &HeaderMap) {
+        if let Some(status) = RateLimitStatus::from_headers(headers) {
+            *self.rate_limit.lock().unwrap() = Some(status);
+        }
+    }
+
+    fn handle_response<Resp: DeserializeOwned>(&self, r: Response<Bytes>) -> PocketResult<Resp> {
+        match r.headers().get(HEADER_XERROR_CODE) {
+            None if !r.status().is_success() => {
+                let (parts, body) = r.into_parts();
+                Err(PocketError::Status {
+                    status: parts.status,
+                    headers: parts.headers,
+                    body: error_body(&body),
+                })
+            }
+            None => serde_json::from_slice(r.body()).map_err(From::from),
+            Some(code) => {
+                let code = code
+                    .to_str()
+                    .ok()
+                    .and_then(|code| code.trim().parse().ok())
+                    .ok_or_else(|| PocketError::invalid_header(HEADER_XERROR_CODE, code))?;
+                let message = match r.headers().get(HEADER_XERROR) {
+                    Some(message) => message
+                        .to_str()
+                        .map_err(|_| PocketError::invalid_header(HEADER_XERROR, message))?,
+                    None => "unknown protocol error",
+                };
+                Err(PocketError::Proto {
+                    code,
+                    message: message.to_string(),
+                    status: Some(r.status()),
+                    headers: r.headers().clone(),
+                })
+            }
+        }
+    }
+}
+
+async fn with_timeout<F: Future>(
+    timeout: Option<Duration>,
+    kind: TimeoutKind,
+    future: F,
+) -> PocketResult<F::Output> {
+    match timeout {
+        Some(duration) => tokio::time::timeout(duration, future)
             .await
+            .map_err(|_| PocketError::Timeout(kind)),
+        None => Ok(future.await),
     }
 }
+
+fn copy_request(request: &Request<Bytes>) -> Request<Body> {
+    let mut copy = Request::new(Body::from(request.body().clone()));
+    *copy.method_mut() = request.method().clone();
+    *copy.uri_mut() = request.uri().clone();
+    *copy.headers_mut() = request.headers().clone();
+    copy
+}
+
+fn is_status_error(status: StatusCode, headers: &HeaderMap) -> bool {
+    !status.is_success() && !headers.contains_key(HEADER_XERROR_CODE)
+}
+
+/// The first `MAX_ERROR_BODY_LEN` bytes of `body`, without a character cut in half.
+fn error_body(body: &[u8]) -> String {
+    let body = &body[..body.len().min(MAX_ERROR_BODY_LEN)];
+    let end = match std::str::from_utf8(body) {
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        _ => body.len(),
+    };
+    String::from_utf8_lossy(&body[..end]).into_owned()
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
@@ -1,6 +1,14 @@
+use crate::rate_limit::RateLimitStatus;
+use crate::send::{ActionOutcome, PocketSendResponse, TagError};
 use hyper::error::Error as HttpError;
+use hyper::header::HeaderValue;
+use hyper::{HeaderMap, StatusCode};
+use std::fmt;
 use std::io::Error as IoError;
 use thiserror::Error;
+use url::ParseError as UrlError;
+
+pub const MAX_ERROR_BODY_LEN: usize = 4096;
 
 #[derive(Error, Debug)]
 pub enum PocketError {
@@ -8,8 +16,361 @@ pub enum PocketError {
     Http(#[from] HttpError),
     #[error(transparent)]
     Json(#[from] serde_json::Error),
-    #[error("{1} (code {0})")]
-    Proto(u16, String),
+    #[error("{message} (code {code})")]
+    Proto {
+        /// Pocket's `X-Error-Code`.
+        code: u16,
+        message: String,
+        /// `None` if the error was detected by the client, e.g. a mismatched OAuth state.
+        status: Option<StatusCode>,
+        headers: HeaderMap,
+    },
     #[error(transparent)]
     Io(#[from] IoError),
+    #[error(transparent)]
+    Url(#[from] UrlError),
+    #[error(transparent)]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+    #[error("{0} timeout elapsed")]
+    Timeout(TimeoutKind),
+    #[error("invalid url: {0}")]
+    InvalidUrl(String),
+    #[error(transparent)]
+    InvalidTag(#[from] TagError),
+    #[error("invalid {name} header: {value:?}")]
+    InvalidHeader { name: &'static str, value: String },
+    #[error("unexpected HTTP status {status}")]
+    Status {
+        status: StatusCode,
+        headers: HeaderMap,
+        /// The start of the response body, at most `MAX_ERROR_BODY_LEN` bytes and
+        /// never ending in a partial character.
+        body: String,
+    },
+    #[error(
+        "{} of {} actions failed",
+        outcomes.iter().filter(|o| !o.is_success()).count(),
+        outcomes.len()
+    )]
+    ActionsFailed {
+        /// The outcome of every action, in the order of the actions.
+        outcomes: Vec<ActionOutcome>,
+    },
+    /// Pocket returned `actual` results (or errors) for `expected` actions.
+    #[error("sent {expected} actions, got {actual} results")]
+    ResultCountMismatch { expected: usize, actual: usize },
+    #[error("batch failed at action {failed_at}: {source}")]
+    BatchFailed {
+        /// The merged responses of the chunks sent before the failed one.
+        completed: PocketSendResponse,
+        /// The index of the first action of the failed chunk.
+        failed_at: usize,
+        source: Box<PocketError>,
+    },
+}
+
+impl PocketError {
+    /// Classifies `Proto` errors by their `X-Error-Code` and `Status` errors by their
+    /// HTTP status. A `Proto` error sent with a rate-limit or maintenance status is
+    /// classified by that status. Returns `None` for transport, decoding and
+    /// client-side errors.
+    pub fn kind(&self) -> Option<PocketErrorKind> {
+        match self {
+            PocketError::Proto {
+                code,
+                status,
+                headers,
+                ..
+            } => match status.and_then(|status| PocketErrorKind::from_status(status, headers)) {
+                Some(kind @ (PocketErrorKind::RateLimited | PocketErrorKind::Maintenance)) => {
+                    Some(kind)
+                }
+                kind => PocketErrorKind::from_code(*code).or(kind),
+            },
+            PocketError::Status {
+                status, headers, ..
+            } => PocketErrorKind::from_status(*status, headers),
+            PocketError::BatchFailed { source, .. } => source.kind(),
+            _ => None,
+        }
+    }
+
+    /// The user has to log in (authorize the app) again.
+    pub fn is_auth_error(&self) -> bool {
+        self.kind().is_some_and(PocketErrorKind::is_auth_error)
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.kind() == Some(PocketErrorKind::RateLimited)
+    }
+
+    /// The same call may succeed later.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            PocketError::Http(_) | PocketError::Transport(_) | PocketError::Timeout(_) => true,
+            PocketError::BatchFailed { source, .. } => source.is_retryable(),
+            _ => self.kind().is_some_and(PocketErrorKind::is_retryable),
+        }
+    }
+
+    /// The request itself was rejected and must be changed before trying again.
+    pub fn is_user_input_error(&self) -> bool {
+        match self {
+            PocketError::InvalidUrl(_) | PocketError::InvalidTag(_) => true,
+            _ => self
+                .kind()
+                .is_some_and(PocketErrorKind::is_user_input_error),
+        }
+    }
+
+    pub(crate) fn invalid_header(name: &'static str, value: &HeaderValue) -> PocketError {
+        PocketError::InvalidHeader {
+            name,
+            value: String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        }
+    }
+}
+
+/// Pocket's documented error conditions.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PocketErrorKind {
+    /// 400 without a more specific error code.
+    InvalidRequest,
+    /// 401 without a more specific error code.
+    AuthenticationFailed,
+    /// 403 without a more specific error code.
+    AccessDenied,
+    /// 403 with an exhausted `X-Limit-*` quota, or 429.
+    RateLimited,
+    /// 503, Pocket's sync server is down for scheduled maintenance.
+    Maintenance,
+    /// Any other 5xx.
+    ServerError,
+    /// Code 107, the access token is missing, invalid or has been revoked.
+    InvalidAccessToken,
+    /// Code 138.
+    MissingConsumerKey,
+    /// Code 152.
+    InvalidConsumerKey,
+    /// Code 158, the user rejected the authorization request.
+    UserRejectedCode,
+    /// Code 159.
+    CodeAlreadyUsed,
+    /// Code 181.
+    InvalidRedirectUri,
+    /// Code 182.
+    MissingCode,
+    /// Code 185.
+    CodeNotFound,
+    /// Code 199.
+    ServerIssue,
+}
+
+impl PocketErrorKind {
+    pub fn from_code(code: u16) -> Option<PocketErrorKind> {
+        match code {
+            107 => Some(PocketErrorKind::InvalidAccessToken),
+            138 => Some(PocketErrorKind::MissingConsumerKey),
+            152 => Some(PocketErrorKind::InvalidConsumerKey),
+            158 => Some(PocketErrorKind::UserRejectedCode),
+            159 => Some(PocketErrorKind::CodeAlreadyUsed),
+            181 => Some(PocketErrorKind::InvalidRedirectUri),
+            182 => Some(PocketErrorKind::MissingCode),
+            185 => Some(PocketErrorKind::CodeNotFound),
+            199 => Some(PocketErrorKind::ServerIssue),
+            _ => None,
+        }
+    }
+
+    pub fn from_status(status: StatusCode, headers: &HeaderMap) -> Option<PocketErrorKind> {
+        match status {
+            StatusCode::BAD_REQUEST => Some(PocketErrorKind::InvalidRequest),
+            StatusCode::UNAUTHORIZED => Some(PocketErrorKind::AuthenticationFailed),
+            StatusCode::FORBIDDEN if is_quota_exhausted(headers) => {
+                Some(PocketErrorKind::RateLimited)
+            }
+            StatusCode::FORBIDDEN => Some(PocketErrorKind::AccessDenied),
+            StatusCode::TOO_MANY_REQUESTS => Some(PocketErrorKind::RateLimited),
+            StatusCode::SERVICE_UNAVAILABLE => Some(PocketErrorKind::Maintenance),
+            status if status.is_server_error() => Some(PocketErrorKind::ServerError),
+            _ => None,
+        }
+    }
+
+    pub fn is_auth_error(self) -> bool {
+        matches!(
+            self,
+            PocketErrorKind::AuthenticationFailed
+                | PocketErrorKind::InvalidAccessToken
+                | PocketErrorKind::InvalidConsumerKey
+                | PocketErrorKind::UserRejectedCode
+                | PocketErrorKind::CodeAlreadyUsed
+                | PocketErrorKind::CodeNotFound
+        )
+    }
+
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            PocketErrorKind::RateLimited
+                | PocketErrorKind::Maintenance
+                | PocketErrorKind::ServerError
+                | PocketErrorKind::ServerIssue
+        )
+    }
+
+    pub fn is_user_input_error(self) -> bool {
+        matches!(
+            self,
+            PocketErrorKind::InvalidRequest
+                | PocketErrorKind::MissingConsumerKey
+                | PocketErrorKind::InvalidRedirectUri
+                | PocketErrorKind::MissingCode
+        )
+    }
+}
+
+fn is_quota_exhausted(headers: &HeaderMap) -> bool {
+    RateLimitStatus::from_headers(headers).is_some_and(|status| {
+        status
+            .user
+            .into_iter()
+            .chain(status.key)
+            .any(|limit| limit.remaining == 0)
+    })
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TimeoutKind {
+    Connect,
+    Read,
+    Total,
+}
+
+impl fmt::Display for TimeoutKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutKind::Connect => f.write_str("connect"),
+            TimeoutKind::Read => f.write_str("read"),
+            TimeoutKind::Total => f.write_str("total"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn proto_error(code: u16, message: &str, status: StatusCode) -> PocketError {
+        PocketError::Proto {
+            code,
+            message: message.to_string(),
+            status: Some(status),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    fn exhausted_quota_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Limit-User-Limit", HeaderValue::from_static("320"));
+        headers.insert("X-Limit-User-Remaining", HeaderValue::from_static("0"));
+        headers.insert("X-Limit-User-Reset", HeaderValue::from_static("60"));
+        headers
+    }
+
+    #[test]
+    fn test_proto_error_kind() {
+        let error = proto_error(152, "Invalid consumer key.", StatusCode::FORBIDDEN);
+
+        assert_eq!(error.kind(), Some(PocketErrorKind::InvalidConsumerKey));
+        assert!(error.is_auth_error());
+        assert!(!error.is_retryable());
+        assert!(!error.is_user_input_error());
+    }
+
+    #[test]
+    fn test_unknown_proto_error_code() {
+        let error = PocketError::Proto {
+            code: 0,
+            message: "State does not match".to_string(),
+            status: None,
+            headers: HeaderMap::new(),
+        };
+
+        assert_eq!(error.kind(), None);
+        assert!(!error.is_auth_error());
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_status_error_kind() {
+        let error = PocketError::Status {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            headers: HeaderMap::new(),
+            body: String::new(),
+        };
+
+        assert_eq!(error.kind(), Some(PocketErrorKind::Maintenance));
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_forbidden_with_exhausted_quota_is_rate_limited() {
+        let error = PocketError::Status {
+            status: StatusCode::FORBIDDEN,
+            headers: exhausted_quota_headers(),
+            body: String::new(),
+        };
+
+        assert!(error.is_rate_limited());
+        assert!(error.is_retryable());
+        assert!(!error.is_auth_error());
+    }
+
+    #[test]
+    fn test_proto_error_with_exhausted_quota_is_rate_limited() {
+        let error = PocketError::Proto {
+            code: 152,
+            message: "Invalid consumer key.".to_string(),
+            status: Some(StatusCode::FORBIDDEN),
+            headers: exhausted_quota_headers(),
+        };
+
+        assert_eq!(error.kind(), Some(PocketErrorKind::RateLimited));
+        assert!(error.is_retryable());
+        assert!(!error.is_auth_error());
+    }
+
+    #[test]
+    fn test_proto_error_during_maintenance() {
+        let error = proto_error(199, "Pocket server issue.", StatusCode::SERVICE_UNAVAILABLE);
+
+        assert_eq!(error.kind(), Some(PocketErrorKind::Maintenance));
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_proto_error_code_wins_over_other_statuses() {
+        let error = proto_error(107, "Invalid access token.", StatusCode::UNAUTHORIZED);
+
+        assert_eq!(error.kind(), Some(PocketErrorKind::InvalidAccessToken));
+    }
+
+    #[test]
+    fn test_forbidden_without_quota_headers_is_access_denied() {
+        let error = PocketError::Status {
+            status: StatusCode::FORBIDDEN,
+            headers: HeaderMap::new(),
+            body: String::new(),
+        };
+
+        assert_eq!(error.kind(), Some(PocketErrorKind::AccessDenied));
+        assert!(!error.is_rate_limited());
+    }
+
+    #[test]
+    fn test_timeout_is_retryable() {
+        assert!(PocketError::Timeout(TimeoutKind::Total).is_retryable());
+        assert_eq!(PocketError::Timeout(TimeoutKind::Total).kind(), None);
+    }
 }
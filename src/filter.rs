@@ -0,0 +1,244 @@
+use crate::get::{PocketItem, PocketItemStatus};
+use crate::{PocketItemHas, PocketResult};
+use futures::future;
+use futures::stream::{BoxStream, Stream, StreamExt};
+use std::fmt;
+use std::ops::Not;
+use std::sync::Arc;
+
+/// A client-side predicate over `PocketItem` fields the `/v3/get` parameters cannot
+/// filter on. Combine filters with `and`, `or` and `!`:
+///
+/// ```
+/// use pocket::filter::ItemFilter;
+/// use pocket::get::PocketItemStatus;
+///
+/// let filter = ItemFilter::Status(PocketItemStatus::Normal)
+///     .and(ItemFilter::IsArticle)
+///     .and(ItemFilter::MaxTimeToRead(10))
+///     .and(ItemFilter::Lang("en".to_string()))
+///     .and(ItemFilter::Tagged("rust".to_string()))
+///     .and(ItemFilter::Tagged("async".to_string()));
+/// ```
+#[derive(Clone)]
+pub enum ItemFilter {
+    All(Vec<ItemFilter>),
+    Any(Vec<ItemFilter>),
+    Not(Box<ItemFilter>),
+    Status(PocketItemStatus),
+    Favorite,
+    IsArticle,
+    HasImage(PocketItemHas),
+    HasVideo(PocketItemHas),
+    MinWordCount(usize),
+    MaxWordCount(usize),
+    /// Items without Pocket's reading time estimate never match.
+    MinTimeToRead(u64),
+    /// Items without Pocket's reading time estimate never match.
+    MaxTimeToRead(u64),
+    /// Case-insensitive language code, e.g. `en`.
+    Lang(String),
+    Tagged(String),
+    /// Case-insensitive substring of any author's name.
+    Author(String),
+    Predicate(Arc<dyn Fn(&PocketItem) -> bool + Send + Sync>),
+}
+
+impl ItemFilter {
+    pub fn predicate<F>(predicate: F) -> ItemFilter
+    where
+        F: Fn(&PocketItem) -> bool + Send + Sync + 'static,
+    {
+        ItemFilter::Predicate(Arc::new(predicate))
+    }
+
+    pub fn and(self, other: ItemFilter) -> ItemFilter {
+        match self {
+            ItemFilter::All(mut filters) => {
+                filters.push(other);
+                ItemFilter::All(filters)
+            }
+            filter => ItemFilter::All(vec![filter, other]),
+        }
+    }
+
+    pub fn or(self, other: ItemFilter) -> ItemFilter {
+        match self {
+            ItemFilter::Any(mut filters) => {
+                filters.push(other);
+                ItemFilter::Any(filters)
+            }
+            filter => ItemFilter::Any(vec![filter, other]),
+        }
+    }
+
+    pub fn matches(&self, item: &PocketItem) -> bool {
+        match self {
+            ItemFilter::All(filters) => filters.iter().all(|f| f.matches(item)),
+            ItemFilter::Any(filters) => filters.iter().any(|f| f.matches(item)),
+            ItemFilter::Not(filter) => !filter.matches(item),
+            ItemFilter::Status(status) => item.status == *status,
+            ItemFilter::Favorite => item.favorite,
+            ItemFilter::IsArticle => item.is_article,
+            ItemFilter::HasImage(has) => item.has_image == *has,
+            ItemFilter::HasVideo(has) => item.has_video == *has,
+            ItemFilter::MinWordCount(min) => item.word_count >= *min,
+            ItemFilter::MaxWordCount(max) => item.word_count <= *max,
+            ItemFilter::MinTimeToRead(min) => item.time_to_read.is_some_and(|t| t >= *min),
+            ItemFilter::MaxTimeToRead(max) => item.time_to_read.is_some_and(|t| t <= *max),
+            ItemFilter::Lang(lang) => item.lang.eq_ignore_ascii_case(lang),
+            ItemFilter::Tagged(tag) => item
+                .tags
+                .iter()
+                .flatten()
+                .any(|item_tag| item_tag.tag == *tag),
+            ItemFilter::Author(name) => {
+                let name = name.to_lowercase();
+                item.authors
+                    .iter()
+                    .flatten()
+                    .any(|author| author.name.to_lowercase().contains(&name))
+            }
+            ItemFilter::Predicate(predicate) => predicate(item),
+        }
+    }
+
+    /// Keeps the matching items.
+    pub fn apply(&self, mut items: Vec<PocketItem>) -> Vec<PocketItem> {
+        items.retain(|item| self.matches(item));
+        items
+    }
+
+    /// Drops the items of `stream` that don't match, passing errors through.
+    pub fn filter_stream<'s, S>(self, stream: S) -> BoxStream<'s, PocketResult<PocketItem>>
+    where
+        S: Stream<Item = PocketResult<PocketItem>> + Send + 's,
+    {
+        stream
+            .filter(move |item| {
+                future::ready(item.as_ref().map_or(true, |item| self.matches(item)))
+            })
+            .boxed()
+    }
+}
+
+impl Not for ItemFilter {
+    type Output = ItemFilter;
+
+    fn not(self) -> ItemFilter {
+        match self {
+            ItemFilter::Not(filter) => *filter,
+            filter => ItemFilter::Not(Box::new(filter)),
+        }
+    }
+}
+
+impl fmt::Debug for ItemFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemFilter::All(filters) => f.debug_tuple("All").field(filters).finish(),
+            ItemFilter::Any(filters) => f.debug_tuple("Any").field(filters).finish(),
+            ItemFilter::Not(filter) => f.debug_tuple("Not").field(filter).finish(),
+            ItemFilter::Status(status) => f.debug_tuple("Status").field(status).finish(),
+            ItemFilter::Favorite => f.write_str("Favorite"),
+            ItemFilter::IsArticle => f.write_str("IsArticle"),
+            ItemFilter::HasImage(has) => f.debug_tuple("HasImage").field(has).finish(),
+            ItemFilter::HasVideo(has) => f.debug_tuple("HasVideo").field(has).finish(),
+            ItemFilter::MinWordCount(min) => f.debug_tuple("MinWordCount").field(min).finish(),
+            ItemFilter::MaxWordCount(max) => f.debug_tuple("MaxWordCount").field(max).finish(),
+            ItemFilter::MinTimeToRead(min) => f.debug_tuple("MinTimeToRead").field(min).finish(),
+            ItemFilter::MaxTimeToRead(max) => f.debug_tuple("MaxTimeToRead").field(max).finish(),
+            ItemFilter::Lang(lang) => f.debug_tuple("Lang").field(lang).finish(),
+            ItemFilter::Tagged(tag) => f.debug_tuple("Tagged").field(tag).finish(),
+            ItemFilter::Author(name) => f.debug_tuple("Author").field(name).finish(),
+            ItemFilter::Predicate(_) => f.write_str("Predicate(..)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::test_item::TestItem;
+    use futures::stream;
+    use serde_json::{json, Value};
+
+    fn test_item(item_id: u64) -> TestItem {
+        TestItem::new(item_id).with(json!({"word_count": "1000", "time_to_read": 5}))
+    }
+
+    fn item(item_id: u64, fields: Value) -> PocketItem {
+        test_item(item_id).with(fields).build()
+    }
+
+    fn tagged_item(item_id: u64, tags: &[&str], fields: Value) -> PocketItem {
+        test_item(item_id).tags(tags).with(fields).build()
+    }
+
+    #[test]
+    fn test_combined_filter() {
+        let filter = ItemFilter::Status(PocketItemStatus::Normal)
+            .and(ItemFilter::IsArticle)
+            .and(ItemFilter::MaxTimeToRead(10))
+            .and(ItemFilter::Lang("EN".to_string()))
+            .and(ItemFilter::Tagged("rust".to_string()))
+            .and(ItemFilter::Tagged("async".to_string()));
+
+        let items = vec![
+            tagged_item(1, &["rust", "async"], json!({})),
+            tagged_item(2, &["rust"], json!({})),
+            tagged_item(3, &["rust", "async"], json!({"time_to_read": 20})),
+            tagged_item(4, &["rust", "async"], json!({"lang": "de"})),
+            tagged_item(5, &["rust", "async"], json!({"status": "1"})),
+            tagged_item(6, &["rust", "async"], json!({"time_to_read": null})),
+        ];
+
+        let ids = filter
+            .apply(items)
+            .iter()
+            .map(|item| item.item_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1]);
+    }
+
+    #[test]
+    fn test_or_and_not() {
+        let filter = (ItemFilter::HasVideo(PocketItemHas::Is).or(ItemFilter::MinWordCount(5000)))
+            .and(!ItemFilter::Favorite);
+
+        assert!(filter.matches(&item(1, json!({"has_video": "2"}))));
+        assert!(filter.matches(&item(2, json!({"word_count": "6000"}))));
+        assert!(!filter.matches(&item(3, json!({"has_video": "1"}))));
+        assert!(!filter.matches(&item(4, json!({"has_video": "2", "favorite": "1"}))));
+    }
+
+    #[test]
+    fn test_author_and_predicate() {
+        let authors =
+            json!({"7": {"item_id": "1", "author_id": "7", "name": "Jane Doe", "url": ""}});
+        let filter = ItemFilter::Author("jane".to_string())
+            .and(ItemFilter::predicate(|item| item.item_id > 1));
+
+        assert!(filter.matches(&item(2, json!({ "authors": authors }))));
+        assert!(!filter.matches(&item(1, json!({ "authors": authors }))));
+        assert!(!filter.matches(&item(2, json!({}))));
+    }
+
+    #[tokio::test]
+    async fn test_filter_stream_keeps_errors() {
+        let items = vec![
+            Ok(item(1, json!({"lang": "en"}))),
+            Ok(item(2, json!({"lang": "de"}))),
+            Err(crate::errors::PocketError::InvalidUrl("test".to_string())),
+        ];
+
+        let filtered = ItemFilter::Lang("en".to_string())
+            .filter_stream(stream::iter(items))
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered[0].as_ref().unwrap().item_id, 1);
+        assert!(filtered[1].is_err());
+    }
+}
@@ -1,26 +1,62 @@
+use crate::schema::ExtraFields;
 use crate::{serialization::*, ItemAuthor, ItemVideo, PocketImage, PocketItemHas};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
 use url::Url;
 
-#[derive(Default, Serialize)]
+/// Parameters of a `Pocket::get` call.
+///
+/// Strings are borrowed or owned, so a `PocketGetRequest<'static>` can be stored,
+/// sent between tasks, or saved as a search and deserialized again later.
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PocketGetRequest<'a> {
-    search: Option<&'a str>,
-    domain: Option<&'a str>,
-    tag: Option<PocketGetTag<'a>>,
+    search: Option<Cow<'a, str>>,
+    domain: Option<Cow<'a, str>>,
+    pub(crate) tag: Option<PocketGetTag<'a>>,
     state: Option<PocketGetState>,
     content_type: Option<PocketGetType>,
     detail_type: Option<PocketGetDetail>,
-    #[serde(serialize_with = "optional_bool_to_int")]
+    #[serde(
+        default,
+        serialize_with = "optional_bool_to_int",
+        deserialize_with = "optional_bool_from_bool_or_int"
+    )]
     favorite: Option<bool>,
-    #[serde(serialize_with = "optional_datetime_to_int")]
+    #[serde(
+        default,
+        serialize_with = "optional_datetime_to_int",
+        deserialize_with = "option_timestamp_from_str_or_int"
+    )]
     since: Option<DateTime<Utc>>,
     sort: Option<PocketGetSort>,
-    #[serde(serialize_with = "optional_to_string")]
+    #[serde(
+        default,
+        serialize_with = "optional_to_string",
+        deserialize_with = "option_from_str_or_int"
+    )]
     count: Option<usize>,
-    #[serde(serialize_with = "optional_to_string")]
-    offset: Option<usize>,
+    #[serde(
+        default,
+        serialize_with = "optional_to_string",
+        deserialize_with = "option_from_str_or_int"
+    )]
+    pub(crate) offset: Option<usize>,
+    #[serde(
+        default,
+        serialize_with = "optional_bool_to_int",
+        deserialize_with = "optional_bool_from_bool_or_int"
+    )]
+    total: Option<bool>,
+    #[serde(
+        default,
+        serialize_with = "optional_bool_to_int",
+        deserialize_with = "optional_bool_from_bool_or_int"
+    )]
+    annotations: Option<bool>,
 }
 
 impl<'a> PocketGetRequest<'a> {
@@ -28,13 +64,38 @@ impl<'a> PocketGetRequest<'a> {
         Default::default()
     }
 
-    pub fn search<'b>(&'b mut self, search: &'a str) -> &'b mut PocketGetRequest<'a> {
-        self.search = Some(search);
+    /// Copies any borrowed strings so the request no longer borrows anything.
+    pub fn into_owned(self) -> PocketGetRequest<'static> {
+        PocketGetRequest {
+            search: self.search.map(|s| Cow::Owned(s.into_owned())),
+            domain: self.domain.map(|s| Cow::Owned(s.into_owned())),
+            tag: self.tag.map(PocketGetTag::into_owned),
+            state: self.state,
+            content_type: self.content_type,
+            detail_type: self.detail_type,
+            favorite: self.favorite,
+            since: self.since,
+            sort: self.sort,
+            count: self.count,
+            offset: self.offset,
+            total: self.total,
+            annotations: self.annotations,
+        }
+    }
+
+    pub fn search<'b, S: Into<Cow<'a, str>>>(
+        &'b mut self,
+        search: S,
+    ) -> &'b mut PocketGetRequest<'a> {
+        self.search = Some(search.into());
         self
     }
 
-    pub fn domain<'b>(&'b mut self, domain: &'a str) -> &'b mut PocketGetRequest<'a> {
-        self.domain = Some(domain);
+    pub fn domain<'b, S: Into<Cow<'a, str>>>(
+        &'b mut self,
+        domain: S,
+    ) -> &'b mut PocketGetRequest<'a> {
+        self.domain = Some(domain.into());
         self
     }
 
@@ -136,16 +197,88 @@ impl<'a> PocketGetRequest<'a> {
     pub fn slice<'b>(&'b mut self, offset: usize, count: usize) -> &'b mut PocketGetRequest<'a> {
         self.offset(offset).count(count)
     }
+
+    /// Asks Pocket to report the number of matching items in `PocketGetResponse::total`.
+    pub fn total<'b>(&'b mut self, total: bool) -> &'b mut PocketGetRequest<'a> {
+        self.total = Some(total);
+        self
+    }
+
+    /// Asks Pocket to include each item's highlights in `PocketItem::annotations`.
+    pub fn annotations<'b>(&'b mut self, annotations: bool) -> &'b mut PocketGetRequest<'a> {
+        self.annotations = Some(annotations);
+        self
+    }
+
+    pub fn with_search<S: Into<Cow<'a, str>>>(mut self, search: S) -> PocketGetRequest<'a> {
+        self.search(search);
+        self
+    }
+
+    pub fn with_domain<S: Into<Cow<'a, str>>>(mut self, domain: S) -> PocketGetRequest<'a> {
+        self.domain(domain);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: PocketGetTag<'a>) -> PocketGetRequest<'a> {
+        self.tag(tag);
+        self
+    }
+
+    pub fn with_state(mut self, state: PocketGetState) -> PocketGetRequest<'a> {
+        self.state(state);
+        self
+    }
+
+    pub fn with_content_type(mut self, content_type: PocketGetType) -> PocketGetRequest<'a> {
+        self.content_type(content_type);
+        self
+    }
+
+    pub fn with_detail_type(mut self, detail_type: PocketGetDetail) -> PocketGetRequest<'a> {
+        self.detail_type(detail_type);
+        self
+    }
+
+    pub fn with_favorite(mut self, fav: bool) -> PocketGetRequest<'a> {
+        self.favorite(fav);
+        self
+    }
+
+    pub fn with_since(mut self, since: DateTime<Utc>) -> PocketGetRequest<'a> {
+        self.since(since);
+        self
+    }
+
+    pub fn with_sort(mut self, sort: PocketGetSort) -> PocketGetRequest<'a> {
+        self.sort(sort);
+        self
+    }
+
+    pub fn with_slice(mut self, offset: usize, count: usize) -> PocketGetRequest<'a> {
+        self.slice(offset, count);
+        self
+    }
+
+    pub fn with_total(mut self, total: bool) -> PocketGetRequest<'a> {
+        self.total(total);
+        self
+    }
+
+    pub fn with_annotations(mut self, annotations: bool) -> PocketGetRequest<'a> {
+        self.annotations(annotations);
+        self
+    }
 }
 
-#[derive(Serialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum PocketGetDetail {
     Simple,
     Complete,
 }
 
-#[derive(Serialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum PocketGetSort {
     Newest,
@@ -154,7 +287,7 @@ pub enum PocketGetSort {
     Site,
 }
 
-#[derive(Serialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum PocketGetState {
     Unread,
@@ -162,15 +295,42 @@ pub enum PocketGetState {
     All,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, PartialEq, Clone)]
 #[serde(untagged)]
 pub enum PocketGetTag<'a> {
     #[serde(serialize_with = "untagged_to_str")]
     Untagged,
-    Tagged(&'a str),
+    Tagged(Cow<'a, str>),
+}
+
+impl<'a> PocketGetTag<'a> {
+    pub fn tagged<S: Into<Cow<'a, str>>>(tag: S) -> PocketGetTag<'a> {
+        PocketGetTag::Tagged(tag.into())
+    }
+
+    pub fn into_owned(self) -> PocketGetTag<'static> {
+        match self {
+            PocketGetTag::Untagged => PocketGetTag::Untagged,
+            PocketGetTag::Tagged(tag) => PocketGetTag::Tagged(Cow::Owned(tag.into_owned())),
+        }
+    }
 }
 
-#[derive(Serialize, Debug, Clone, Copy)]
+impl<'de, 'a> Deserialize<'de> for PocketGetTag<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<PocketGetTag<'a>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let tag = String::deserialize(deserializer)?;
+        Ok(if tag == UNTAGGED {
+            PocketGetTag::Untagged
+        } else {
+            PocketGetTag::Tagged(Cow::Owned(tag))
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum PocketGetType {
     Article,
@@ -178,17 +338,104 @@ pub enum PocketGetType {
     Image,
 }
 
-#[derive(Deserialize, Debug, PartialEq)]
-pub struct PocketGetResponse {
-    #[serde(deserialize_with = "vec_from_map")]
-    pub list: Vec<PocketItem>,
+/// The response of a `/v3/get` call, with its items decoded as an `L`.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(bound(deserialize = "L: ItemList"))]
+pub struct PocketGetResponse<L = Vec<PocketItem>> {
+    #[serde(deserialize_with = "L::deserialize_list")]
+    pub list: L,
+    /// 1 on success, 2 when the list is empty.
     pub status: u16,
+    /// Whether `list` holds complete item data or only what changed since `since`.
     #[serde(deserialize_with = "bool_from_int")]
     pub complete: bool,
     pub error: Option<String>,
     pub search_meta: PocketSearchMeta,
+    /// Server time of this response, to be passed as `since` on the next
+    /// incremental fetch.
     #[serde(deserialize_with = "int_date_unix_timestamp_format")]
     pub since: DateTime<Utc>,
+    /// Number of items matching the request, present when it was made with `total(true)`.
+    #[serde(default, deserialize_with = "option_from_str")]
+    pub total: Option<usize>,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
+}
+
+/// A list decoded item by item, keeping the items that decoded when others did not.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LenientList<T> {
+    pub items: Vec<T>,
+    pub failures: Vec<ItemDecodeError>,
+}
+
+/// An item of a `LenientList` that could not be decoded.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ItemDecodeError {
+    /// The item's `item_id`, if it has a readable one.
+    pub item_id: Option<u64>,
+    pub raw: Value,
+    pub error: String,
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for LenientList<T> {
+    fn deserialize<D>(deserializer: D) -> Result<LenientList<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let entries = match Value::deserialize(deserializer)? {
+            Value::Array(entries) => entries,
+            Value::Object(entries) => entries.into_iter().map(|(_, entry)| entry).collect(),
+            other => {
+                return Err(serde::de::Error::invalid_value(
+                    serde::de::Unexpected::Other(&format!("{:?}", other)),
+                    &"object or array",
+                ))
+            }
+        };
+
+        let mut list = LenientList {
+            items: Vec::with_capacity(entries.len()),
+            failures: vec![],
+        };
+        for entry in entries {
+            match T::deserialize(&entry) {
+                Ok(item) => list.items.push(item),
+                Err(e) => list.failures.push(ItemDecodeError {
+                    item_id: entry.get("item_id").and_then(|id| match id {
+                        Value::String(id) => id.parse().ok(),
+                        id => id.as_u64(),
+                    }),
+                    raw: entry,
+                    error: e.to_string(),
+                }),
+            }
+        }
+        Ok(list)
+    }
+}
+
+/// A `PocketGetResponse` whose `list` is decoded leniently.
+pub type PocketGetLenientResponse = PocketGetResponse<LenientList<PocketItem>>;
+
+/// How the `list` of a `PocketGetResponse` is decoded. Pocket sends it as a map
+/// keyed by item id, or as an empty array when there are no items.
+pub trait ItemList: Sized {
+    fn deserialize_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+impl<T: DeserializeOwned + Clone + std::fmt::Debug> ItemList for Vec<T> {
+    fn deserialize_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        vec_from_map(deserializer)
+    }
+}
+
+impl<T: DeserializeOwned> ItemList for LenientList<T> {
+    fn deserialize_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        LenientList::deserialize(deserializer)
+    }
 }
 
 #[derive(Deserialize, Debug, PartialEq, Clone)]
@@ -241,6 +488,13 @@ pub struct PocketItem {
     pub amp_url: Option<Url>,
     #[serde(default, deserialize_with = "try_url_from_string")]
     pub top_image_url: Option<Url>,
+    /// Highlights, only returned for requests made with `annotations(true)`.
+    #[serde(default, deserialize_with = "optional_vec_from_map")]
+    pub annotations: Option<Vec<ItemAnnotation>>,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
 }
 #[derive(Deserialize, Debug, PartialEq, Clone)]
 pub struct ItemImage {
@@ -252,6 +506,10 @@ pub struct ItemImage {
     pub width: u16,
     #[serde(deserialize_with = "from_str")]
     pub height: u16,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Clone)]
@@ -259,10 +517,19 @@ pub struct DomainMetaData {
     pub name: Option<String>,
     pub logo: String,
     pub greyscale_logo: String,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
 }
-#[derive(Deserialize, Debug, PartialEq)]
+#[derive(Deserialize, Debug, PartialEq, Clone)]
 pub struct PocketSearchMeta {
-    search_type: String,
+    /// How `search` was matched, `"normal"` for plain requests.
+    pub search_type: String,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
@@ -275,26 +542,52 @@ pub enum PocketItemStatus {
     Deleted,
 }
 
+/// A highlighted passage of an item.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+pub struct ItemAnnotation {
+    pub annotation_id: String,
+    #[serde(deserialize_with = "from_str")]
+    pub item_id: u64,
+    /// The highlighted text.
+    pub quote: String,
+    /// Where `quote` is in the article, as a diff-match-patch patch against its text.
+    pub patch: String,
+    #[serde(deserialize_with = "from_str_or_int")]
+    pub version: u32,
+    #[serde(deserialize_with = "option_string_date_format")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
+}
+
 #[derive(Deserialize, Debug, PartialEq, Clone)]
 pub struct ItemTag {
     #[serde(deserialize_with = "from_str")]
     pub item_id: u64,
     pub tag: String,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::utils::remove_whitespace;
+    use crate::utils::test_item::TestItem;
     use chrono::TimeZone;
+    use serde_json::json;
 
     // Get
     // PocketGetRequest
     #[test]
     fn test_serialize_get_request() {
         let request = &PocketGetRequest {
-            search: Some("search"),
-            domain: Some("domain"),
+            search: Some("search".into()),
+            domain: Some("domain".into()),
 
             tag: Some(PocketGetTag::Untagged),
             state: Some(PocketGetState::All),
@@ -306,6 +599,8 @@ mod test {
             sort: Some(PocketGetSort::Newest),
             count: Some(1),
             offset: Some(2),
+            total: Some(true),
+            annotations: Some(true),
         };
 
         let actual = serde_json::to_string(request).unwrap();
@@ -323,18 +618,20 @@ mod test {
                         "since": "{since}",
                         "sort": "{sort}",
                         "count": "{count}",
-                        "offset": "{offset}"
+                        "offset": "{offset}",
+                        "total": "1",
+                        "annotations": "1"
                     }}
                "#,
-            search = request.search.unwrap(),
-            domain = request.domain.unwrap(),
-            tag = to_inner_json_string(&request.tag.as_ref()),
-            state = to_inner_json_string(&request.state.unwrap()),
-            content_type = to_inner_json_string(&request.content_type.unwrap()),
-            detail_type = to_inner_json_string(&request.detail_type.unwrap()),
+            search = request.search.as_deref().unwrap(),
+            domain = request.domain.as_deref().unwrap(),
+            tag = to_inner_json_string(request.tag.as_ref()),
+            state = to_inner_json_string(request.state.unwrap()),
+            content_type = to_inner_json_string(request.content_type.unwrap()),
+            detail_type = to_inner_json_string(request.detail_type.unwrap()),
             favorite = if request.favorite.unwrap() { 1 } else { 0 },
-            since = request.since.unwrap().timestamp().to_string(),
-            sort = to_inner_json_string(&request.sort.unwrap()),
+            since = request.since.unwrap().timestamp(),
+            sort = to_inner_json_string(request.sort.unwrap()),
             count = request.count.unwrap(),
             offset = request.offset.unwrap(),
         ));
@@ -342,6 +639,79 @@ mod test {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_get_request_round_trip() {
+        let request = PocketGetRequest::new()
+            .with_search(String::from("async"))
+            .with_tag(PocketGetTag::tagged("rust"))
+            .with_state(PocketGetState::Unread)
+            .with_content_type(PocketGetType::Video)
+            .with_favorite(true)
+            .with_since(Utc.timestamp_opt(1584221353, 0).unwrap())
+            .with_sort(PocketGetSort::Oldest)
+            .with_slice(10, 50)
+            .with_total(true);
+
+        let json = serde_json::to_string(&request).unwrap();
+        let actual: PocketGetRequest<'static> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(actual, request);
+    }
+
+    #[test]
+    fn test_deserialize_saved_search() {
+        let saved = r#"{"tag": "_untagged_", "contentType": "video", "count": "20"}"#;
+
+        let actual: PocketGetRequest<'static> = serde_json::from_str(saved).unwrap();
+
+        let mut expected = PocketGetRequest::new();
+        expected.tag(PocketGetTag::Untagged).videos().count(20);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_saved_search_toml_with_native_types() {
+        let saved = r#"
+            search = "async"
+            tag = "rust"
+            state = "unread"
+            favorite = true
+            since = 1584221353
+            sort = "oldest"
+            count = 20
+            offset = 0
+            total = false
+        "#;
+
+        let actual: PocketGetRequest<'static> = toml::from_str(saved).unwrap();
+
+        let expected = PocketGetRequest::new()
+            .with_search("async")
+            .with_tag(PocketGetTag::tagged("rust"))
+            .with_state(PocketGetState::Unread)
+            .with_favorite(true)
+            .with_since(Utc.timestamp_opt(1584221353, 0).unwrap())
+            .with_sort(PocketGetSort::Oldest)
+            .with_slice(0, 20)
+            .with_total(false);
+        assert_eq!(actual, expected);
+
+        let reloaded: PocketGetRequest<'static> =
+            toml::from_str(&toml::to_string(&actual).unwrap()).unwrap();
+        assert_eq!(reloaded, expected);
+    }
+
+    #[test]
+    fn test_get_request_into_owned() {
+        let search = String::from("rust");
+        let request = PocketGetRequest::new().with_search(search.as_str());
+
+        let owned: PocketGetRequest<'static> = request.clone().into_owned();
+        drop(search);
+
+        assert_eq!(owned.search.as_deref(), Some("rust"));
+    }
+
     fn to_inner_json_string<T: Serialize>(value: T) -> String {
         serde_json::to_value(value)
             .unwrap()
@@ -361,8 +731,11 @@ mod test {
             error: None,
             search_meta: PocketSearchMeta {
                 search_type: "normal".to_string(),
+                extra: Default::default(),
             },
-            since: Utc.timestamp(1584221353, 0),
+            since: Utc.timestamp_opt(1584221353, 0).unwrap(),
+            total: None,
+            extra: Default::default(),
         };
         let response = remove_whitespace(&format!(
             r#"
@@ -397,8 +770,11 @@ mod test {
             error: None,
             search_meta: PocketSearchMeta {
                 search_type: "normal".to_string(),
+                extra: Default::default(),
             },
-            since: Utc.timestamp(1584221353, 0),
+            since: Utc.timestamp_opt(1584221353, 0).unwrap(),
+            total: None,
+            extra: Default::default(),
         };
         let response = remove_whitespace(&format!(
             r#"
@@ -423,4 +799,74 @@ mod test {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_lenient_list_keeps_decodable_items() {
+        let response = format!(
+            r#"{{"status":1,"complete":1,"list":{{"1":{},"2":{},"3":{}}},"error":null,"search_meta":{{"search_type":"normal"}},"since":1584221353,"total":"3"}}"#,
+            TestItem::new(1),
+            TestItem::new(2).without("sort_id"),
+            TestItem::new(3).with(json!({"sort_id": 2})),
+        );
+
+        let actual: PocketGetLenientResponse = serde_json::from_str(&response).unwrap();
+
+        let ids = actual
+            .list
+            .items
+            .iter()
+            .map(|i| i.item_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 3]);
+        assert_eq!(actual.list.failures.len(), 1);
+        let failure = &actual.list.failures[0];
+        assert_eq!(failure.item_id, Some(2));
+        assert_eq!(failure.raw["resolved_id"], "2");
+        assert!(failure.error.contains("sort_id"));
+        assert_eq!(actual.status, 1);
+        assert!(actual.complete);
+        assert_eq!(actual.since, Utc.timestamp_opt(1584221353, 0).unwrap());
+        assert_eq!(actual.total, Some(3));
+    }
+
+    #[test]
+    fn test_lenient_list_accepts_empty_array() {
+        let actual: LenientList<PocketItem> = serde_json::from_str("[]").unwrap();
+
+        assert!(actual.items.is_empty() && actual.failures.is_empty());
+    }
+
+    #[test]
+    fn test_deserialize_item_annotations() {
+        let item = |annotations: Value| {
+            TestItem::new(1)
+                .with(json!({ "annotations": annotations }))
+                .build()
+        };
+        let annotation = json!({
+            "annotation_id": "a1b2",
+            "item_id": "1",
+            "quote": "Highlighted text",
+            "patch": "@@ -1,4 +1,30 @@",
+            "version": "2",
+            "created_at": "2020-03-14 21:00:12",
+        });
+        let expected = ItemAnnotation {
+            annotation_id: "a1b2".to_string(),
+            item_id: 1,
+            quote: "Highlighted text".to_string(),
+            patch: "@@ -1,4 +1,30 @@".to_string(),
+            version: 2,
+            created_at: Some(Utc.with_ymd_and_hms(2020, 3, 14, 21, 0, 12).unwrap()),
+            extra: Default::default(),
+        };
+
+        let from_array = item(json!([annotation]));
+        let from_map = item(json!({ "a1b2": annotation }));
+        let without = item(Value::Null);
+
+        assert_eq!(from_array.annotations, Some(vec![expected.clone()]));
+        assert_eq!(from_map.annotations, Some(vec![expected]));
+        assert_eq!(without.annotations, None);
+    }
 }
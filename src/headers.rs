@@ -1,3 +1,9 @@
 pub const HEADER_XACCEPT: &str = "X-Accept";
 pub const HEADER_XERROR: &str = "X-Error";
 pub const HEADER_XERROR_CODE: &str = "X-Error-Code";
+pub const HEADER_XLIMIT_USER_LIMIT: &str = "X-Limit-User-Limit";
+pub const HEADER_XLIMIT_USER_REMAINING: &str = "X-Limit-User-Remaining";
+pub const HEADER_XLIMIT_USER_RESET: &str = "X-Limit-User-Reset";
+pub const HEADER_XLIMIT_KEY_LIMIT: &str = "X-Limit-Key-Limit";
+pub const HEADER_XLIMIT_KEY_REMAINING: &str = "X-Limit-Key-Remaining";
+pub const HEADER_XLIMIT_KEY_RESET: &str = "X-Limit-Key-Reset";
@@ -1,25 +1,48 @@
 use add::*;
-use client::PocketClient;
+use client::{CallOptions, ClientOptions, PocketClient};
 use errors::PocketError;
+use filter::ItemFilter;
+use futures::stream::{self, BoxStream, StreamExt};
 use futures::TryFutureExt;
 use get::*;
 use hyper::http::uri::InvalidUri;
 use hyper::Uri;
+use rate_limit::{RateLimitStatus, TokenBucket};
+use retry::RetryPolicy;
+use schema::ExtraFields;
 use send::*;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serialization::*;
+use std::collections::HashSet;
 use std::convert::TryInto;
 use std::result::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use sync::{SyncDelta, SyncResponse, SyncState};
+use tag_query::TagQuery;
+use transport::Transport;
 use url::Url;
+use utils::with_trailing_slash;
 
 pub mod add;
 pub mod auth;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 mod client;
 pub mod errors;
+pub mod filter;
 pub mod get;
 mod headers;
+pub mod query;
+pub mod rate_limit;
+pub mod retry;
+pub mod schema;
 pub mod send;
 mod serialization;
+pub mod sync;
+pub mod tag_query;
+pub mod transport;
 mod utils;
 
 pub type PocketResult<T> = Result<T, PocketError>;
@@ -38,6 +61,10 @@ pub struct PocketImage {
     pub height: u16,
     pub credit: String,
     pub caption: String,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Clone)]
@@ -57,6 +84,10 @@ pub struct ItemVideo {
     pub vid: String,
     #[serde(rename = "type", deserialize_with = "from_str")]
     pub vtype: u16,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Clone)]
@@ -67,6 +98,10 @@ pub struct ItemAuthor {
     pub author_id: u64,
     pub name: String,
     pub url: String,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
@@ -87,19 +122,23 @@ pub struct PocketUserRequest<'a, T> {
     request: T,
 }
 
+pub const DEFAULT_BASE_URL: &str = "https://getpocket.com/v3/";
+
 pub struct Pocket {
     consumer_key: String,
     access_token: String,
+    base_url: Url,
+    send_method: SendMethod,
     client: PocketClient,
 }
 
 impl Pocket {
     pub fn new(consumer_key: &str, access_token: &str) -> Pocket {
-        Pocket {
-            consumer_key: consumer_key.to_string(),
-            access_token: access_token.to_string(),
-            client: PocketClient::new(),
-        }
+        PocketBuilder::new(consumer_key, access_token).build()
+    }
+
+    pub fn builder(consumer_key: &str, access_token: &str) -> PocketBuilder {
+        PocketBuilder::new(consumer_key, access_token)
     }
 
     #[inline]
@@ -107,58 +146,1566 @@ impl Pocket {
         &self.access_token
     }
 
+    #[inline]
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Rate-limit quota reported by the most recent response, if it carried any.
+    pub fn last_rate_limit(&self) -> Option<RateLimitStatus> {
+        self.client.last_rate_limit()
+    }
+
     pub async fn add(&self, request: &PocketAddRequest<'_>) -> PocketResult<PocketAddedItem> {
         let body = &PocketUserRequest {
-            consumer_key: &*self.consumer_key,
-            access_token: &*self.access_token,
+            consumer_key: &self.consumer_key,
+            access_token: &self.access_token,
             request,
         };
 
+        let url = self.endpoint("add")?;
         self.client
-            .post("https://getpocket.com/v3/add", &body)
+            .post(url.as_str(), &body, CallOptions::default())
             .map_ok(|v: PocketAddResponse| v.item)
             .await
     }
 
     pub async fn get(&self, request: &PocketGetRequest<'_>) -> PocketResult<Vec<PocketItem>> {
+        self.get_response(request).map_ok(|v| v.list).await
+    }
+
+    /// Like `get`, but with `timeout` instead of the client's total timeout, e.g. for
+    /// large `detailType=complete` fetches.
+    pub async fn get_with_timeout(
+        &self,
+        request: &PocketGetRequest<'_>,
+        timeout: Duration,
+    ) -> PocketResult<Vec<PocketItem>> {
+        self.post_get(request, Some(timeout))
+            .map_ok(|v: PocketGetResponse| v.list)
+            .await
+    }
+
+    /// Like `get`, but items that fail to decode are returned in `list.failures`
+    /// instead of failing the whole call.
+    pub async fn get_lenient(
+        &self,
+        request: &PocketGetRequest<'_>,
+    ) -> PocketResult<PocketGetLenientResponse> {
+        self.post_get(request, None).await
+    }
+
+    /// Like `get`, but only returns the items matching `filter`.
+    pub async fn get_filtered(
+        &self,
+        request: &PocketGetRequest<'_>,
+        filter: &ItemFilter,
+    ) -> PocketResult<Vec<PocketItem>> {
+        self.get(request).map_ok(|items| filter.apply(items)).await
+    }
+
+    /// Returns the items matching both `request` and `query`, a tag of `request`
+    /// being and-ed with `query`.
+    ///
+    /// Makes one `get` call per tag needed to cover all matches (a single untagged
+    /// call if there is none, e.g. for `NOT rust`), merges the results by `item_id`
+    /// and evaluates the rest of `query` locally. `count` and `offset` apply to each
+    /// of those calls.
+    pub async fn get_tagged(
+        &self,
+        request: &PocketGetRequest<'_>,
+        query: &TagQuery,
+    ) -> PocketResult<Vec<PocketItem>> {
+        let mut request = request.clone();
+        let query = match request.tag.take() {
+            Some(tag) => TagQuery::from(tag).and(query.clone()),
+            None => query.clone(),
+        };
+        if query.is_single_tag() {
+            request.tag = query.plan().and_then(|mut tags| tags.pop());
+            return self.get(&request).await;
+        }
+
+        let tags = match query.plan() {
+            Some(tags) => tags.into_iter().map(Some).collect(),
+            None => vec![None],
+        };
+        request.complete();
+        let mut seen = HashSet::new();
+        let mut items = vec![];
+        for tag in tags {
+            request.tag = tag;
+            for item in self.get(&request).await? {
+                if seen.insert(item.item_id) && query.matches(&item) {
+                    items.push(item);
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    /// Like `get`, but also returns the response metadata such as `since` and `total`.
+    pub async fn get_response(
+        &self,
+        request: &PocketGetRequest<'_>,
+    ) -> PocketResult<PocketGetResponse> {
+        self.post_get(request, None).await
+    }
+
+    /// Fetches all items (unread, archived and deleted) that changed since `state`
+    /// and advances it to the server time of the response. `state` is left as is
+    /// if the call fails.
+    pub async fn sync(&self, state: &mut SyncState) -> PocketResult<SyncDelta> {
+        let mut request = PocketGetRequest::new();
+        request.state(PocketGetState::All).complete();
+        if let Some(since) = state.since {
+            request.since(since);
+        }
+
+        let response: SyncResponse = self.post_get(&request, None).await?;
+        let delta = SyncDelta::new(response.list, state.since);
+        state.since = Some(response.since);
+        Ok(delta)
+    }
+
+    async fn post_get<Resp: DeserializeOwned>(
+        &self,
+        request: &PocketGetRequest<'_>,
+        timeout: Option<Duration>,
+    ) -> PocketResult<Resp> {
         let body = &PocketUserRequest {
-            consumer_key: &*self.consumer_key,
-            access_token: &*self.access_token,
+            consumer_key: &self.consumer_key,
+            access_token: &self.access_token,
             request,
         };
 
+        let url = self.endpoint("get")?;
         self.client
-            .post("https://getpocket.com/v3/get", &body)
-            .map_ok(|v: PocketGetResponse| v.list)
+            .post(
+                url.as_str(),
+                &body,
+                CallOptions {
+                    idempotent: true,
+                    timeout,
+                },
+            )
             .await
     }
 
+    /// Streams every item matching `request`, fetching `page_size` items per call
+    /// starting at the request's offset. The request's `count` is ignored.
+    ///
+    /// Every page after the first starts `page_size / 4` items (at least one)
+    /// before the end of the previous one, and items already yielded are skipped.
+    /// Items pushed to the next page by changes made while paging are not returned
+    /// twice, and up to that many items removed while paging don't make later items
+    /// move past the next page's start. Removing more items between two pages can
+    /// still skip items. Stops after the first short page or the first error.
+    ///
+    /// Panics if `page_size` is zero.
+    pub fn get_stream<'p, 'a: 'p>(
+        &'p self,
+        request: &PocketGetRequest<'a>,
+        page_size: usize,
+    ) -> BoxStream<'p, PocketResult<PocketItem>> {
+        assert!(page_size > 0, "page_size must be positive");
+
+        let start = request.offset.unwrap_or(0);
+        let overlap = (page_size / 4).max(1).min(page_size - 1);
+        let pages = GetPages {
+            offset: start,
+            request: request.clone(),
+            seen: HashSet::new(),
+            done: false,
+        };
+
+        stream::unfold(pages, move |mut pages| async move {
+            if pages.done {
+                return None;
+            }
+
+            let offset = pages.offset.saturating_sub(overlap).max(start);
+            let mut request = pages.request.clone();
+            request.slice(offset, page_size);
+            let items = match self.get(&request).await {
+                Ok(items) => items,
+                Err(e) => {
+                    pages.done = true;
+                    return Some((vec![Err(e)], pages));
+                }
+            };
+
+            pages.done = items.len() < page_size;
+            pages.offset = offset + items.len();
+            let seen = &mut pages.seen;
+            let items = items
+                .into_iter()
+                .filter(|item| seen.insert(item.item_id))
+                .map(Ok)
+                .collect::<Vec<_>>();
+            Some((items, pages))
+        })
+        .flat_map(stream::iter)
+        .boxed()
+    }
+
     pub async fn send(&self, request: &PocketSendRequest<'_>) -> PocketResult<PocketSendResponse> {
-        let data = serde_json::to_string(request.actions)?;
-        let params = &[
-            ("consumer_key", &*self.consumer_key),
-            ("access_token", &*self.access_token),
-            ("actions", &data),
-        ];
+        let mut url = self.endpoint("send")?;
+        match self.send_method {
+            SendMethod::Post => {
+                let body = &PocketUserRequest {
+                    consumer_key: &self.consumer_key,
+                    access_token: &self.access_token,
+                    request,
+                };
+                self.client
+                    .post(url.as_str(), &body, CallOptions::default())
+                    .await
+            }
+            SendMethod::Get => {
+                let data = serde_json::to_string(request.actions)?;
+                let params = &[
+                    ("consumer_key", &*self.consumer_key),
+                    ("access_token", &*self.access_token),
+                    ("actions", &data),
+                ];
+                url.query_pairs_mut().extend_pairs(params);
 
-        let url = Url::parse_with_params("https://getpocket.com/v3/send", params).unwrap();
+                self.client
+                    .get(url_to_uri(&url)?, CallOptions::default())
+                    .await
+            }
+        }
+    }
+
+    pub async fn archive(&self, item_id: u64) -> PocketResult<()> {
+        self.send_action(PocketSendAction::Archive {
+            item_id,
+            time: None,
+        })
+        .await
+    }
+
+    pub async fn readd(&self, item_id: u64) -> PocketResult<()> {
+        self.send_action(PocketSendAction::Readd {
+            item_id,
+            time: None,
+        })
+        .await
+    }
+
+    pub async fn favorite(&self, item_id: u64) -> PocketResult<()> {
+        self.send_action(PocketSendAction::Favorite {
+            item_id,
+            time: None,
+        })
+        .await
+    }
+
+    pub async fn unfavorite(&self, item_id: u64) -> PocketResult<()> {
+        self.send_action(PocketSendAction::Unfavorite {
+            item_id,
+            time: None,
+        })
+        .await
+    }
+
+    pub async fn delete(&self, item_id: u64) -> PocketResult<()> {
+        self.send_action(PocketSendAction::Delete {
+            item_id,
+            time: None,
+        })
+        .await
+    }
+
+    pub async fn add_tags(&self, item_id: u64, tags: Tags) -> PocketResult<()> {
+        self.send_action(PocketSendAction::TagsAdd {
+            item_id,
+            tags,
+            time: None,
+        })
+        .await
+    }
+
+    pub async fn remove_tags(&self, item_id: u64, tags: Tags) -> PocketResult<()> {
+        self.send_action(PocketSendAction::TagsRemove {
+            item_id,
+            tags,
+            time: None,
+        })
+        .await
+    }
+
+    pub async fn replace_tags(&self, item_id: u64, tags: Tags) -> PocketResult<()> {
+        self.send_action(PocketSendAction::TagsReplace {
+            item_id,
+            tags,
+            time: None,
+        })
+        .await
+    }
+
+    pub async fn clear_tags(&self, item_id: u64) -> PocketResult<()> {
+        self.send_action(PocketSendAction::TagsClear {
+            item_id,
+            time: None,
+        })
+        .await
+    }
+
+    /// Fails with `PocketError::InvalidTag` if a tag is empty or contains a comma.
+    pub async fn rename_tag(&self, old_tag: &str, new_tag: &str) -> PocketResult<()> {
+        self.send_action(PocketSendAction::TagRename {
+            old_tag: Tag::new(old_tag)?,
+            new_tag: Tag::new(new_tag)?,
+            time: None,
+        })
+        .await
+    }
+
+    /// Fails with `PocketError::InvalidTag` if `tag` is empty or contains a comma.
+    pub async fn delete_tag(&self, tag: &str) -> PocketResult<()> {
+        self.send_action(PocketSendAction::TagDelete {
+            tag: Tag::new(tag)?,
+            time: None,
+        })
+        .await
+    }
 
-        self.client.get(url_to_uri(&url).unwrap()).await
+    /// Sends a single action, failing with `PocketError::ActionsFailed` if Pocket
+    /// rejects it.
+    async fn send_action(&self, action: PocketSendAction) -> PocketResult<()> {
+        let actions = [&action];
+        self.send(&PocketSendRequest { actions: &actions })
+            .await?
+            .ensure_success(&actions)
+            .map(|_| ())
     }
 
-    pub fn filter(&self) -> PocketGetRequest {
+    /// Sends the actions of `batch`, `chunk_size` actions per `send` call, and
+    /// returns the merged responses with results in the order of the actions.
+    ///
+    /// Stops at the first failed call with `PocketError::BatchFailed`, which holds
+    /// the responses of the chunks sent before it; those stay applied.
+    pub async fn send_batch(&self, batch: &SendBatch) -> PocketResult<PocketSendResponse> {
+        let mut merged = PocketSendResponse {
+            status: 1,
+            action_results: vec![],
+            action_errors: vec![],
+            extra: Default::default(),
+        };
+        for actions in batch.chunks() {
+            match self.send(&PocketSendRequest { actions: &actions }).await {
+                Ok(response) => merged.merge(response),
+                Err(e) => {
+                    return Err(PocketError::BatchFailed {
+                        failed_at: merged.action_results.len(),
+                        completed: merged,
+                        source: Box::new(e),
+                    })
+                }
+            }
+        }
+        Ok(merged)
+    }
+
+    pub fn filter(&self) -> PocketGetRequest<'_> {
         PocketGetRequest::new()
     }
+
+    fn endpoint(&self, method: &str) -> PocketResult<Url> {
+        self.base_url.join(method).map_err(From::from)
+    }
+}
+
+struct GetPages<'a> {
+    request: PocketGetRequest<'a>,
+    offset: usize,
+    seen: HashSet<u64>,
+    done: bool,
+}
+
+pub struct PocketBuilder {
+    consumer_key: String,
+    access_token: String,
+    base_url: Url,
+    send_method: SendMethod,
+    options: ClientOptions,
+}
+
+impl PocketBuilder {
+    pub fn new(consumer_key: &str, access_token: &str) -> PocketBuilder {
+        PocketBuilder {
+            consumer_key: consumer_key.to_string(),
+            access_token: access_token.to_string(),
+            base_url: Url::parse(DEFAULT_BASE_URL).unwrap(),
+            send_method: SendMethod::default(),
+            options: ClientOptions::default(),
+        }
+    }
+
+    /// Sets the URL all API methods (`add`, `get`, `send`) are resolved against,
+    /// e.g. `http://localhost:8080/v3/` for a local stand-in server.
+    pub fn base_url(&mut self, base_url: Url) -> &mut PocketBuilder {
+        self.base_url = with_trailing_slash(base_url);
+        self
+    }
+
+    /// Defaults to `SendMethod::Post`.
+    pub fn send_method(&mut self, send_method: SendMethod) -> &mut PocketBuilder {
+        self.send_method = send_method;
+        self
+    }
+
+    /// Makes every call wait for the rate-limit window to reset once the remaining
+    /// user or key quota reported by the last response drops below `min_remaining`.
+    pub fn throttle(&mut self, min_remaining: u32) -> &mut PocketBuilder {
+        self.options.min_remaining = Some(min_remaining);
+        self
+    }
+
+    /// Caps how long `throttle` waits for a window to reset, whatever the server
+    /// reports. Defaults to `DEFAULT_MAX_THROTTLE_DELAY`.
+    pub fn max_throttle_delay(&mut self, max_delay: Duration) -> &mut PocketBuilder {
+        self.options.max_throttle_delay = Some(max_delay);
+        self
+    }
+
+    /// Takes a token from `limiter` before every call.
+    pub fn rate_limiter(&mut self, limiter: Arc<TokenBucket>) -> &mut PocketBuilder {
+        self.options.rate_limiter = Some(limiter);
+        self
+    }
+
+    pub fn retry(&mut self, retry: RetryPolicy) -> &mut PocketBuilder {
+        self.options.retry = retry;
+        self
+    }
+
+    /// Sends requests through `transport` instead of the default `HyperTransport`.
+    pub fn transport<T: Transport + 'static>(&mut self, transport: T) -> &mut PocketBuilder {
+        self.options.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Timeout for establishing a connection. Only applies to the default transport.
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut PocketBuilder {
+        self.options.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for waiting on the response headers and on every chunk of the body.
+    pub fn read_timeout(&mut self, timeout: Duration) -> &mut PocketBuilder {
+        self.options.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for a whole request attempt, from sending it to reading the full body.
+    /// `Pocket::get_with_timeout` overrides it for a single call.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut PocketBuilder {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    pub fn build(&self) -> Pocket {
+        Pocket {
+            consumer_key: self.consumer_key.clone(),
+            access_token: self.access_token.clone(),
+            base_url: self.base_url.clone(),
+            send_method: self.send_method,
+            client: PocketClient::new(self.options.clone()),
+        }
+    }
 }
 
-fn url_to_uri(url: &Url) -> Result<Uri, InvalidUri> {
-    url.as_str().try_into()
+fn url_to_uri(url: &Url) -> PocketResult<Uri> {
+    url.as_str()
+        .try_into()
+        .map_err(|e: InvalidUri| PocketError::InvalidUrl(e.to_string()))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use chrono::{TimeZone, Utc};
     use utils::remove_whitespace;
+    use utils::test_item::TestItem;
+    use utils::test_server::{TestResponse, TestServer};
+
+    const EMPTY_GET_RESPONSE: &str = r#"{"status":1,"complete":1,"list":[],"error":null,"search_meta":{"search_type":"normal"},"since":1584221353}"#;
+
+    #[test]
+    fn test_builder_base_url_adds_trailing_slash() {
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(Url::parse("http://localhost:8080/v3").unwrap())
+            .build();
+
+        assert_eq!(
+            pocket.endpoint("get").unwrap().as_str(),
+            "http://localhost:8080/v3/get"
+        );
+    }
+
+    #[test]
+    fn test_default_base_url() {
+        let pocket = Pocket::new("consumer_key", "access_token");
+
+        assert_eq!(pocket.base_url().as_str(), DEFAULT_BASE_URL);
+    }
+
+    #[tokio::test]
+    async fn test_get_uses_base_url() {
+        let server = TestServer::start(vec![TestResponse::json(EMPTY_GET_RESPONSE)]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+
+        let items = pocket.get(&PocketGetRequest::new()).await.unwrap();
+
+        assert!(items.is_empty());
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, hyper::Method::POST);
+        assert_eq!(requests[0].uri, "/v3/get");
+        assert!(requests[0]
+            .body
+            .contains(r#""consumer_key":"consumer_key""#));
+    }
+
+    #[tokio::test]
+    async fn test_last_rate_limit() {
+        let server = TestServer::start(vec![TestResponse::json(EMPTY_GET_RESPONSE)
+            .header("X-Limit-User-Limit", "320")
+            .header("X-Limit-User-Remaining", "319")
+            .header("X-Limit-User-Reset", "3600")
+            .header("X-Limit-Key-Limit", "10000")
+            .header("X-Limit-Key-Remaining", "9000")
+            .header("X-Limit-Key-Reset", "86400")]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+        assert_eq!(pocket.last_rate_limit(), None);
+
+        pocket.get(&PocketGetRequest::new()).await.unwrap();
+
+        let status = pocket.last_rate_limit().unwrap();
+        assert_eq!(status.user.unwrap().remaining, 319);
+        assert_eq!(status.key.unwrap().remaining, 9000);
+    }
+
+    #[tokio::test]
+    async fn test_throttle_caps_wait_for_reset() {
+        let server = TestServer::start(vec![TestResponse::json(EMPTY_GET_RESPONSE)
+            .header("X-Limit-User-Limit", "320")
+            .header("X-Limit-User-Remaining", "0")
+            .header("X-Limit-User-Reset", "86400")]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .throttle(1)
+            .max_throttle_delay(Duration::from_millis(10))
+            .build();
+
+        pocket.get(&PocketGetRequest::new()).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), pocket.get(&PocketGetRequest::new()))
+            .await
+            .expect("throttle wait was not capped")
+            .unwrap();
+
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_throttle_ignores_unrepresentable_reset() {
+        let server = TestServer::start(vec![TestResponse::json(EMPTY_GET_RESPONSE)
+            .header("X-Limit-User-Limit", "320")
+            .header("X-Limit-User-Remaining", "0")
+            .header("X-Limit-User-Reset", "18446744073709551615")]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .throttle(1)
+            .build();
+
+        pocket.get(&PocketGetRequest::new()).await.unwrap();
+        pocket.get(&PocketGetRequest::new()).await.unwrap();
+
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_is_shared() {
+        let server = TestServer::start(vec![TestResponse::json(EMPTY_GET_RESPONSE)]);
+        let limiter = Arc::new(TokenBucket::new(1, Duration::from_secs(3600)));
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .rate_limiter(limiter.clone())
+            .build();
+
+        pocket.get(&PocketGetRequest::new()).await.unwrap();
+
+        assert!(limiter.try_acquire().is_err());
+    }
+
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            jitter: false,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_retries_server_errors() {
+        let server = TestServer::start(vec![
+            TestResponse::json("").status(503),
+            TestResponse::json("").status(502),
+            TestResponse::json(EMPTY_GET_RESPONSE),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(fast_retry())
+            .build();
+
+        let items = pocket.get(&PocketGetRequest::new()).await.unwrap();
+
+        assert!(items.is_empty());
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_gives_up_after_max_attempts() {
+        let server = TestServer::start(vec![TestResponse::json("").status(503)]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(fast_retry())
+            .build();
+
+        assert!(pocket.get(&PocketGetRequest::new()).await.is_err());
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_honors_retry_after() {
+        let server = TestServer::start(vec![
+            TestResponse::json("")
+                .status(503)
+                .header("Retry-After", "0"),
+            TestResponse::json(EMPTY_GET_RESPONSE),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(RetryPolicy {
+                base_delay: Duration::from_secs(3600),
+                ..Default::default()
+            })
+            .build();
+
+        pocket.get(&PocketGetRequest::new()).await.unwrap();
+
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_caps_retry_after_at_max_delay() {
+        let server = TestServer::start(vec![
+            TestResponse::json("")
+                .status(503)
+                .header("Retry-After", "86400"),
+            TestResponse::json(EMPTY_GET_RESPONSE),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(RetryPolicy {
+                max_delay: Duration::from_millis(10),
+                ..fast_retry()
+            })
+            .build();
+
+        tokio::time::timeout(Duration::from_secs(5), pocket.get(&PocketGetRequest::new()))
+            .await
+            .expect("Retry-After was not capped")
+            .unwrap();
+
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_add_is_not_retried_by_default() {
+        let server = TestServer::start(vec![TestResponse::json("").status(503)]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(fast_retry())
+            .build();
+        let url = Url::parse("https://example.com").unwrap();
+
+        assert!(pocket.add(&PocketAddRequest::new(&url)).await.is_err());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_is_retried_when_opted_in() {
+        let server = TestServer::start(vec![
+            TestResponse::json("").status(500),
+            TestResponse::json(r#"{"status":1,"action_results":[],"action_errors":[]}"#),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(RetryPolicy {
+                retry_non_idempotent: true,
+                ..fast_retry()
+            })
+            .build();
+
+        pocket
+            .send(&PocketSendRequest { actions: &[] })
+            .await
+            .unwrap();
+
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    struct FakeTransport {
+        body: &'static str,
+        uris: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl Transport for FakeTransport {
+        fn send(
+            &self,
+            request: hyper::Request<hyper::Body>,
+        ) -> futures::future::BoxFuture<'static, PocketResult<hyper::Response<hyper::Body>>>
+        {
+            self.uris.lock().unwrap().push(request.uri().to_string());
+            let body = self.body;
+            Box::pin(async move { Ok(hyper::Response::new(hyper::Body::from(body))) })
+        }
+    }
+
+    /// Responds with a 502 whose body never ends.
+    struct EndlessBodyTransport;
+
+    impl Transport for EndlessBodyTransport {
+        fn send(
+            &self,
+            _request: hyper::Request<hyper::Body>,
+        ) -> futures::future::BoxFuture<'static, PocketResult<hyper::Response<hyper::Body>>>
+        {
+            let chunks = stream::repeat("x".repeat(1024)).map(Ok::<_, std::io::Error>);
+            let response = hyper::Response::builder()
+                .status(502)
+                .body(hyper::Body::wrap_stream(chunks))
+                .unwrap();
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    struct FailingTransport;
+
+    impl Transport for FailingTransport {
+        fn send(
+            &self,
+            _request: hyper::Request<hyper::Body>,
+        ) -> futures::future::BoxFuture<'static, PocketResult<hyper::Response<hyper::Body>>>
+        {
+            let error = std::io::Error::other("offline");
+            Box::pin(async move { Err(PocketError::Transport(Box::new(error))) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_through_custom_transport() {
+        let uris = Arc::new(std::sync::Mutex::new(vec![]));
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .transport(FakeTransport {
+                body: EMPTY_GET_RESPONSE,
+                uris: uris.clone(),
+            })
+            .build();
+
+        let items = pocket.get(&PocketGetRequest::new()).await.unwrap();
+
+        assert!(items.is_empty());
+        assert_eq!(*uris.lock().unwrap(), vec!["https://getpocket.com/v3/get"]);
+    }
+
+    #[tokio::test]
+    async fn test_custom_transport_error() {
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .transport(FailingTransport)
+            .retry(RetryPolicy::none())
+            .build();
+
+        match pocket.get(&PocketGetRequest::new()).await {
+            Err(PocketError::Transport(e)) => assert_eq!(e.to_string(), "offline"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_total_timeout() {
+        let server = TestServer::start(vec![
+            TestResponse::json(EMPTY_GET_RESPONSE).delay(Duration::from_millis(500))
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(RetryPolicy::none())
+            .timeout(Duration::from_millis(50))
+            .build();
+
+        let result = pocket.get(&PocketGetRequest::new()).await;
+
+        assert!(matches!(
+            result,
+            Err(PocketError::Timeout(errors::TimeoutKind::Total))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_overrides_total_timeout() {
+        let server = TestServer::start(vec![
+            TestResponse::json(EMPTY_GET_RESPONSE).delay(Duration::from_millis(100))
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(RetryPolicy::none())
+            .timeout(Duration::from_millis(10))
+            .build();
+
+        let items = pocket
+            .get_with_timeout(&PocketGetRequest::new(), Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert!(items.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_timeout() {
+        let server = TestServer::start(vec![
+            TestResponse::json(EMPTY_GET_RESPONSE).delay(Duration::from_millis(500))
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(RetryPolicy::none())
+            .read_timeout(Duration::from_millis(50))
+            .build();
+
+        let result = pocket.get(&PocketGetRequest::new()).await;
+
+        assert!(matches!(
+            result,
+            Err(PocketError::Timeout(errors::TimeoutKind::Read))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_are_retried() {
+        let server = TestServer::start(vec![
+            TestResponse::json(EMPTY_GET_RESPONSE).delay(Duration::from_millis(500)),
+            TestResponse::json(EMPTY_GET_RESPONSE),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(fast_retry())
+            .timeout(Duration::from_millis(50))
+            .build();
+
+        pocket.get(&PocketGetRequest::new()).await.unwrap();
+
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    async fn get_with_response(response: TestResponse) -> PocketResult<Vec<PocketItem>> {
+        let server = TestServer::start(vec![response]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(RetryPolicy::none())
+            .build();
+
+        pocket.get(&PocketGetRequest::new()).await
+    }
+
+    #[tokio::test]
+    async fn test_proto_error() {
+        let result = get_with_response(
+            TestResponse::json("")
+                .status(401)
+                .header("X-Error-Code", "107")
+                .header("X-Error", "Invalid access token"),
+        )
+        .await;
+
+        match result {
+            Err(PocketError::Proto {
+                code,
+                message,
+                status,
+                ..
+            }) => {
+                assert_eq!(code, 107);
+                assert_eq!(message, "Invalid access token");
+                assert_eq!(status, Some(hyper::StatusCode::UNAUTHORIZED));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_malformed_error_code_header() {
+        let result = get_with_response(
+            TestResponse::json("")
+                .status(400)
+                .header("X-Error-Code", "not-a-number")
+                .header("X-Error", "Missing consumer key"),
+        )
+        .await;
+
+        match result {
+            Err(PocketError::InvalidHeader { name, value }) => {
+                assert_eq!(name, "X-Error-Code");
+                assert_eq!(value, "not-a-number");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_non_utf8_error_header() {
+        let result = get_with_response(
+            TestResponse::json("")
+                .status(400)
+                .header("X-Error-Code", "138")
+                .raw_header("X-Error", b"Missing \xff consumer key"),
+        )
+        .await;
+
+        match result {
+            Err(PocketError::InvalidHeader { name, .. }) => assert_eq!(name, "X-Error"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_error_keeps_body() {
+        let html = "<html><body>502 Bad Gateway</body></html>";
+        let result = get_with_response(
+            TestResponse::json(html)
+                .status(502)
+                .header("Content-Type", "text/html"),
+        )
+        .await;
+
+        match result {
+            Err(PocketError::Status {
+                status,
+                headers,
+                body,
+            }) => {
+                assert_eq!(status, hyper::StatusCode::BAD_GATEWAY);
+                assert!(headers
+                    .get_all("Content-Type")
+                    .iter()
+                    .any(|v| v == "text/html"));
+                assert_eq!(body, html);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_error_body_stops_reading_at_limit() {
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .transport(EndlessBodyTransport)
+            .retry(RetryPolicy::none())
+            .build();
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(5), pocket.get(&PocketGetRequest::new()))
+                .await
+                .expect("error body was read to the end");
+
+        match result {
+            Err(PocketError::Status { status, body, .. }) => {
+                assert_eq!(status, hyper::StatusCode::BAD_GATEWAY);
+                assert_eq!(body.len(), errors::MAX_ERROR_BODY_LEN);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_error_body_is_cut_on_char_boundary() {
+        let body = format!("{}é", "x".repeat(errors::MAX_ERROR_BODY_LEN - 1));
+        let result = get_with_response(TestResponse::json(&body).status(500)).await;
+
+        match result {
+            Err(PocketError::Status { body, .. }) => {
+                assert_eq!(body, "x".repeat(errors::MAX_ERROR_BODY_LEN - 1));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_error_body_is_bounded() {
+        let result =
+            get_with_response(TestResponse::json(&"x".repeat(10 * 1024)).status(401)).await;
+
+        match result {
+            Err(PocketError::Status { status, body, .. }) => {
+                assert_eq!(status, hyper::StatusCode::UNAUTHORIZED);
+                assert_eq!(body.len(), errors::MAX_ERROR_BODY_LEN);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_with_invalid_url() {
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(Url::parse("file:///tmp/v3/").unwrap())
+            .build();
+
+        let result = pocket.send(&PocketSendRequest { actions: &[] }).await;
+
+        assert!(matches!(result, Err(PocketError::InvalidUrl(_))));
+    }
+
+    #[tokio::test]
+    async fn test_get_with_invalid_url() {
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(Url::parse("file:///tmp/v3/").unwrap())
+            .build();
+
+        let result = pocket.get(&PocketGetRequest::new()).await;
+
+        assert!(matches!(result, Err(PocketError::InvalidUrl(_))));
+    }
+
+    #[tokio::test]
+    async fn test_send_uses_base_url() {
+        let server = TestServer::start(vec![TestResponse::json(
+            r#"{"status":1,"action_results":[],"action_errors":[]}"#,
+        )]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+
+        pocket
+            .send(&PocketSendRequest { actions: &[] })
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].uri, "/v3/send");
+    }
+
+    #[tokio::test]
+    async fn test_send_posts_json_body() {
+        let server = TestServer::start(vec![TestResponse::json(
+            r#"{"status":1,"action_results":[true,true],"action_errors":[null,null]}"#,
+        )]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+        let archive = PocketSendAction::Archive {
+            item_id: 1,
+            time: Utc.timestamp_opt(1584221353, 0).single(),
+        };
+        let tags_add = PocketSendAction::TagsAdd {
+            item_id: 2,
+            tags: "one,two".parse().unwrap(),
+            time: None,
+        };
+
+        pocket
+            .send(&PocketSendRequest {
+                actions: &[&archive, &tags_add],
+            })
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, hyper::Method::POST);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&requests[0].body).unwrap(),
+            serde_json::json!({
+                "consumer_key": "consumer_key",
+                "access_token": "access_token",
+                "actions": [
+                    {"action": "archive", "item_id": "1", "time": "1584221353"},
+                    {"action": "tags_add", "item_id": "2", "tags": "one,two", "time": null},
+                ],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_with_get_method_uses_query_string() {
+        let server = TestServer::start(vec![TestResponse::json(
+            r#"{"status":1,"action_results":[true],"action_errors":[null]}"#,
+        )]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .send_method(SendMethod::Get)
+            .build();
+        let archive = PocketSendAction::Archive {
+            item_id: 1,
+            time: None,
+        };
+
+        pocket
+            .send(&PocketSendRequest {
+                actions: &[&archive],
+            })
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, hyper::Method::GET);
+        assert!(requests[0].body.is_empty());
+        let url = Url::parse(&format!("http://localhost{}", requests[0].uri)).unwrap();
+        assert_eq!(url.path(), "/v3/send");
+        let actions = url
+            .query_pairs()
+            .find(|(key, _)| key == "actions")
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+        assert_eq!(
+            actions,
+            r#"[{"action":"archive","item_id":"1","time":null}]"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_item_methods_send_one_action() {
+        let server = TestServer::start(vec![TestResponse::json(
+            r#"{"status":1,"action_results":[true],"action_errors":[null]}"#,
+        )]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+
+        let tags = || Tags::new(["one", "two"]).unwrap();
+        pocket.archive(1).await.unwrap();
+        pocket.readd(2).await.unwrap();
+        pocket.favorite(3).await.unwrap();
+        pocket.unfavorite(4).await.unwrap();
+        pocket.delete(5).await.unwrap();
+        pocket.add_tags(6, tags()).await.unwrap();
+        pocket.remove_tags(7, tags()).await.unwrap();
+        pocket.replace_tags(8, tags()).await.unwrap();
+        pocket.clear_tags(9).await.unwrap();
+        pocket.rename_tag("one", "1").await.unwrap();
+        pocket.delete_tag("two").await.unwrap();
+
+        let actions = server
+            .requests()
+            .iter()
+            .map(|request| serde_json::from_str::<serde_json::Value>(&request.body).unwrap())
+            .map(|body| body["actions"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            actions,
+            vec![
+                serde_json::json!([{"action": "archive", "item_id": "1", "time": null}]),
+                serde_json::json!([{"action": "readd", "item_id": "2", "time": null}]),
+                serde_json::json!([{"action": "favorite", "item_id": "3", "time": null}]),
+                serde_json::json!([{"action": "unfavorite", "item_id": "4", "time": null}]),
+                serde_json::json!([{"action": "delete", "item_id": "5", "time": null}]),
+                serde_json::json!([{"action": "tags_add", "item_id": "6", "tags": "one,two", "time": null}]),
+                serde_json::json!([{"action": "tags_remove", "item_id": "7", "tags": "one,two", "time": null}]),
+                serde_json::json!([{"action": "tags_replace", "item_id": "8", "tags": "one,two", "time": null}]),
+                serde_json::json!([{"action": "tags_clear", "item_id": "9", "time": null}]),
+                serde_json::json!([{"action": "tag_rename", "old_tag": "one", "new_tag": "1", "time": null}]),
+                serde_json::json!([{"action": "tag_delete", "tag": "two", "time": null}]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tag_methods_reject_invalid_tags() {
+        let server = TestServer::start(vec![TestResponse::json(
+            r#"{"status":1,"action_results":[true],"action_errors":[null]}"#,
+        )]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+
+        assert!(matches!(
+            pocket.rename_tag(" ", "1").await,
+            Err(PocketError::InvalidTag(TagError::Empty))
+        ));
+        assert!(matches!(
+            pocket.rename_tag("one", "a,b").await,
+            Err(PocketError::InvalidTag(TagError::Comma(_)))
+        ));
+        assert!(matches!(
+            pocket.delete_tag("").await,
+            Err(PocketError::InvalidTag(TagError::Empty))
+        ));
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_item_method_fails_on_false_result() {
+        let server = TestServer::start(vec![TestResponse::json(
+            r#"{"status":1,"action_results":[false],"action_errors":[{"code":422,"message":"Invalid item","type":"Unprocessable Entity"}]}"#,
+        )]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+
+        match pocket.favorite(1).await {
+            Err(PocketError::ActionsFailed { outcomes }) => {
+                assert_eq!(
+                    outcomes,
+                    vec![ActionOutcome::Failed {
+                        code: 422,
+                        message: "Invalid item".to_string(),
+                        error_type: "Unprocessable Entity".to_string(),
+                    }]
+                );
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_merges_chunks_in_order() {
+        let server = TestServer::start(vec![
+            TestResponse::json(
+                r#"{"status":1,"action_results":[true,false],"action_errors":[null,{"code":422,"message":"Invalid","type":"Unprocessable Entity"}]}"#,
+            ),
+            TestResponse::json(r#"{"status":1,"action_results":[true],"action_errors":[null]}"#),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+        let mut batch = SendBatch::new();
+        batch
+            .archive(1)
+            .favorite(2)
+            .tag_delete("old")
+            .unwrap()
+            .chunk_size(2);
+
+        let response = pocket.send_batch(&batch).await.unwrap();
+
+        assert_eq!(
+            response.action_results,
+            vec![
+                SendActionResult::Success,
+                SendActionResult::Failure,
+                SendActionResult::Success
+            ]
+        );
+        assert_eq!(response.action_errors.len(), 3);
+        assert!(response.action_errors[1].is_some());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_returns_completed_chunks_on_failure() {
+        let server = TestServer::start(vec![
+            TestResponse::json(
+                r#"{"status":1,"action_results":[true,false],"action_errors":[null,{"code":422,"message":"Invalid","type":"Unprocessable Entity"}]}"#,
+            ),
+            TestResponse::json("{}").status(500),
+            TestResponse::json(r#"{"status":1,"action_results":[true],"action_errors":[null]}"#),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(RetryPolicy::none())
+            .build();
+        let mut batch = SendBatch::new();
+        batch
+            .archive(1)
+            .favorite(2)
+            .delete(3)
+            .readd(4)
+            .tag_delete("old")
+            .unwrap()
+            .chunk_size(2);
+
+        match pocket.send_batch(&batch).await {
+            Err(PocketError::BatchFailed {
+                completed,
+                failed_at,
+                source,
+            }) => {
+                assert_eq!(failed_at, 2);
+                assert_eq!(
+                    completed.action_results,
+                    vec![SendActionResult::Success, SendActionResult::Failure]
+                );
+                assert!(completed.action_errors[1].is_some());
+                assert!(matches!(*source, PocketError::Status { status, .. } if status == 500));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_response_returns_metadata() {
+        let server = TestServer::start(vec![TestResponse::json(
+            r#"{"status":1,"complete":0,"list":[],"error":null,"search_meta":{"search_type":"normal"},"since":1584221353,"total":"42"}"#,
+        )]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+
+        let response = pocket
+            .get_response(pocket.filter().total(true))
+            .await
+            .unwrap();
+
+        assert!(!response.complete);
+        assert_eq!(response.search_meta.search_type, "normal");
+        assert_eq!(response.since.timestamp(), 1584221353);
+        assert_eq!(response.total, Some(42));
+        assert!(server.requests()[0].body.contains(r#""total":"1""#));
+    }
+
+    #[tokio::test]
+    async fn test_sync_advances_since_from_response() {
+        let server = TestServer::start(vec![
+            TestResponse::json(&get_response_with_items(&[1])),
+            TestResponse::json(
+                r#"{"status":1,"complete":0,"list":{"1":{"item_id":"1","status":"2"}},"error":null,"search_meta":{"search_type":"normal"},"since":1584221400}"#,
+            ),
+            TestResponse::json("").status(500),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(RetryPolicy::none())
+            .build();
+        let mut state = SyncState::new();
+
+        let delta = pocket.sync(&mut state).await.unwrap();
+        assert_eq!(delta.added.len(), 1);
+        assert_eq!(state.since.unwrap().timestamp(), 1584221353);
+
+        let delta = pocket.sync(&mut state).await.unwrap();
+        assert_eq!(delta.deleted, vec![1]);
+        assert_eq!(state.since.unwrap().timestamp(), 1584221400);
+
+        assert!(pocket.sync(&mut state).await.is_err());
+        assert_eq!(state.since.unwrap().timestamp(), 1584221400);
+
+        let requests = server.requests();
+        assert!(requests[0].body.contains(r#""state":"all""#));
+        assert!(!requests[0].body.contains(r#""since":"1"#));
+        assert!(requests[1].body.contains(r#""since":"1584221353""#));
+        assert!(requests[2].body.contains(r#""since":"1584221400""#));
+    }
+
+    #[tokio::test]
+    async fn test_get_filtered() {
+        let server = TestServer::start(vec![TestResponse::json(&get_response_with_items(&[
+            1, 2, 3,
+        ]))]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+
+        let items = pocket
+            .get_filtered(
+                &PocketGetRequest::new(),
+                &ItemFilter::predicate(|item| item.item_id != 2),
+            )
+            .await
+            .unwrap();
+
+        let ids = items.iter().map(|item| item.item_id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_get_tagged_merges_single_tag_calls() {
+        let server = TestServer::start(vec![
+            TestResponse::json(&get_response_with_tagged_items(&[
+                (1, &["async", "rust"]),
+                (2, &["async"]),
+                (3, &["async", "rust", "read-later"]),
+            ])),
+            TestResponse::json(&get_response_with_tagged_items(&[
+                (1, &["async", "rust"]),
+                (4, &["tokio", "rust"]),
+            ])),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+        let query = TagQuery::parse("(async OR tokio) AND NOT read-later").unwrap();
+
+        let items = pocket
+            .get_tagged(pocket.filter().unread(), &query)
+            .await
+            .unwrap();
+
+        let ids = items.iter().map(|item| item.item_id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2, 4]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        for (request, tag) in requests.iter().zip(&["async", "tokio"]) {
+            assert!(request.body.contains(&format!(r#""tag":"{}""#, tag)));
+            assert!(request.body.contains(r#""state":"unread""#));
+            assert!(request.body.contains(r#""detailType":"complete""#));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_tagged_ands_request_tag() {
+        let server = TestServer::start(vec![TestResponse::json(&get_response_with_tagged_items(
+            &[(1, &["rust"]), (2, &["rust", "async"])],
+        ))]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+
+        let items = pocket
+            .get_tagged(
+                pocket.filter().tag(PocketGetTag::tagged("rust")),
+                &!TagQuery::tag("async"),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].item_id, 1);
+        assert!(server.requests()[0].body.contains(r#""tag":"rust""#));
+    }
+
+    #[tokio::test]
+    async fn test_get_lenient_reports_failures() {
+        let response =
+            get_response_with_items(&[1, 2]).replacen(r#""lang":"en""#, r#""lang":null"#, 1);
+        let server = TestServer::start(vec![TestResponse::json(&response)]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+
+        assert!(pocket.get(&PocketGetRequest::new()).await.is_err());
+        let response = pocket.get_lenient(&PocketGetRequest::new()).await.unwrap();
+
+        assert_eq!(response.status, 1);
+        assert_eq!(response.since, Utc.timestamp_opt(1584221353, 0).unwrap());
+        let list = response.list;
+        assert_eq!(list.items.len(), 1);
+        assert_eq!(list.items[0].item_id, 2);
+        assert_eq!(list.failures.len(), 1);
+        assert_eq!(list.failures[0].item_id, Some(1));
+    }
+
+    fn get_response_with_items(ids: &[u64]) -> String {
+        get_response_with_tagged_items(&ids.iter().map(|&id| (id, &[][..])).collect::<Vec<_>>())
+    }
+
+    fn get_response_with_tagged_items(items: &[(u64, &[&str])]) -> String {
+        let items = items
+            .iter()
+            .map(|(id, tags)| TestItem::new(*id).tags(tags).to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            r#"{{"status":1,"complete":1,"list":[{}],"error":null,"search_meta":{{"search_type":"normal"}},"since":1584221353}}"#,
+            items
+        )
+    }
+
+    #[tokio::test]
+    async fn test_get_stream_pages_until_short_page() {
+        let server = TestServer::start(vec![
+            TestResponse::json(&get_response_with_items(&[1, 2])),
+            TestResponse::json(&get_response_with_items(&[2, 3])),
+            TestResponse::json(&get_response_with_items(&[3, 4])),
+            TestResponse::json(&get_response_with_items(&[4])),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+
+        let ids = pocket
+            .get_stream(pocket.filter().sort_by_newest(), 2)
+            .map(|item| item.unwrap().item_id)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        for (request, offset) in requests.iter().zip(&["0", "1", "2", "3"]) {
+            assert!(request.body.contains(r#""count":"2""#));
+            assert!(request.body.contains(&format!(r#""offset":"{}""#, offset)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_stream_keeps_items_moved_back_by_removals() {
+        // Item 1 is archived after the first page, so item 5 moves back from offset 14 to 13.
+        let server = TestServer::start(vec![
+            TestResponse::json(&get_response_with_items(&[1, 2, 3, 4])),
+            TestResponse::json(&get_response_with_items(&[5, 6, 7])),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+
+        let ids = pocket
+            .get_stream(pocket.filter().offset(10), 4)
+            .map(|item| item.unwrap().item_id)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(ids, vec![1, 2, 3, 4, 5, 6, 7]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].body.contains(r#""offset":"10""#));
+        assert!(requests[1].body.contains(r#""offset":"13""#));
+    }
+
+    #[tokio::test]
+    async fn test_get_stream_stops_on_empty_page() {
+        let server = TestServer::start(vec![
+            TestResponse::json(&get_response_with_items(&[1, 2])),
+            TestResponse::json(EMPTY_GET_RESPONSE),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .build();
+
+        let items = pocket
+            .get_stream(&PocketGetRequest::new(), 2)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_stream_stops_after_error() {
+        let server = TestServer::start(vec![
+            TestResponse::json(&get_response_with_items(&[1, 2])),
+            TestResponse::json("").status(500),
+        ]);
+        let pocket = Pocket::builder("consumer_key", "access_token")
+            .base_url(server.url.clone())
+            .retry(RetryPolicy::none())
+            .build();
+
+        let items = pocket
+            .get_stream(&PocketGetRequest::new(), 2)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(items.len(), 3);
+        assert!(items[0].is_ok() && items[1].is_ok());
+        assert!(matches!(items[2], Err(PocketError::Status { .. })));
+        assert_eq!(server.requests().len(), 2);
+    }
 
     // ItemImage
     #[test]
@@ -168,6 +1715,7 @@ mod test {
             src: Url::parse("http://localhost").ok(),
             width: 3,
             height: 4,
+            extra: Default::default(),
         };
         let response = remove_whitespace(&format!(
             r#"
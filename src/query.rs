@@ -0,0 +1,503 @@
+//! A compact query language for `PocketGetRequest`, e.g.
+//! `tag:rust is:unread is:fav type:video domain:github.com since:7d sort:oldest limit:50 "search words"`.
+//!
+//! Supported terms:
+//!
+//! * `tag:NAME`, `tag:_untagged_` or `is:untagged`
+//! * `is:unread`, `is:archived`, `is:all`, `is:fav` and `-is:fav`
+//! * `type:article`, `type:video`, `type:image`
+//! * `domain:HOST`
+//! * `since:DATE` with `DATE` either `YYYY-MM-DD` or a number of `s`, `m`, `h`, `d` or `w` ago
+//! * `sort:newest`, `sort:oldest`, `sort:title`, `sort:site`
+//! * `detail:simple`, `detail:complete`
+//! * `limit:N`, `offset:N`
+//! * anything else, bare or `"quoted"`, is searched for
+//!
+//! Terms the API has no parameter for, like unknown keys or negations, are reported
+//! in `ParsedQuery::unsupported` instead of failing the whole query. Giving the same
+//! filter twice, e.g. two tags or `is:unread is:archived`, is an error.
+
+use crate::get::{
+    PocketGetDetail, PocketGetRequest, PocketGetSort, PocketGetState, PocketGetTag, PocketGetType,
+};
+use crate::serialization::UNTAGGED;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::HashSet;
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParsedQuery {
+    pub request: PocketGetRequest<'static>,
+    /// Terms that could not be turned into request parameters, in query order.
+    pub unsupported: Vec<UnsupportedTerm>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnsupportedTerm {
+    /// 1-based column of the term's first character.
+    pub column: usize,
+    /// The term as written in the query.
+    pub term: String,
+    pub reason: &'static str,
+}
+
+#[derive(Error, Debug, PartialEq, Clone)]
+#[error("{kind} at column {column}")]
+pub struct QueryParseError {
+    /// 1-based column of the offending term or quote.
+    pub column: usize,
+    pub kind: QueryErrorKind,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum QueryErrorKind {
+    UnterminatedQuote,
+    EmptyValue {
+        key: String,
+    },
+    InvalidValue {
+        key: String,
+        value: String,
+    },
+    UnexpectedToken {
+        token: String,
+    },
+    UnexpectedEnd,
+    /// A filter that takes a single value, e.g. `tag` or `since`, was given again.
+    DuplicateFilter {
+        filter: &'static str,
+    },
+}
+
+impl fmt::Display for QueryErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryErrorKind::UnterminatedQuote => f.write_str("unterminated quote"),
+            QueryErrorKind::EmptyValue { key } => write!(f, "missing value for {:?}", key),
+            QueryErrorKind::InvalidValue { key, value } => {
+                write!(f, "invalid value {:?} for {:?}", value, key)
+            }
+            QueryErrorKind::UnexpectedToken { token } => write!(f, "unexpected {:?}", token),
+            QueryErrorKind::UnexpectedEnd => f.write_str("unexpected end of query"),
+            QueryErrorKind::DuplicateFilter { filter } => {
+                write!(f, "more than one {} filter", filter)
+            }
+        }
+    }
+}
+
+impl ParsedQuery {
+    pub fn parse(input: &str) -> Result<ParsedQuery, QueryParseError> {
+        ParsedQuery::parse_at(input, Utc::now())
+    }
+
+    /// Parses `input`, resolving relative `since` terms against `now`.
+    pub fn parse_at(input: &str, now: DateTime<Utc>) -> Result<ParsedQuery, QueryParseError> {
+        let mut builder = QueryBuilder::default();
+        for term in tokenize(input)? {
+            builder.apply(term, now)?;
+        }
+        Ok(builder.finish())
+    }
+}
+
+struct Term {
+    column: usize,
+    text: String,
+    negated: bool,
+    key: Option<String>,
+    value: String,
+}
+
+impl Term {
+    fn invalid(&self) -> QueryParseError {
+        QueryParseError {
+            column: self.column,
+            kind: QueryErrorKind::InvalidValue {
+                key: self.key.clone().unwrap_or_default(),
+                value: self.value.clone(),
+            },
+        }
+    }
+
+    fn unsupported(self, reason: &'static str) -> UnsupportedTerm {
+        UnsupportedTerm {
+            column: self.column,
+            term: self.text,
+            reason,
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Term>, QueryParseError> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let mut terms = vec![];
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        if chars[pos].is_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        let start = pos;
+        let negated = chars[pos] == '-' && chars.get(pos + 1).is_some_and(|c| !c.is_whitespace());
+        if negated {
+            pos += 1;
+        }
+
+        let (first, quoted) = read_word(&chars, &mut pos, true)?;
+        let (key, value) = if !quoted && chars.get(pos) == Some(&':') {
+            pos += 1;
+            let (value, quoted) = read_word(&chars, &mut pos, false)?;
+            if value.is_empty() && !quoted {
+                return Err(QueryParseError {
+                    column: start + 1,
+                    kind: QueryErrorKind::EmptyValue { key: first },
+                });
+            }
+            (Some(first.to_lowercase()), value)
+        } else {
+            (None, first)
+        };
+
+        terms.push(Term {
+            column: start + 1,
+            text: chars[start..pos].iter().collect(),
+            negated,
+            key,
+            value,
+        });
+    }
+
+    Ok(terms)
+}
+
+/// Reads a `"quoted"` or bare word at `pos`. Bare keys end at a colon,
+/// bare values only at whitespace.
+fn read_word(
+    chars: &[char],
+    pos: &mut usize,
+    is_key: bool,
+) -> Result<(String, bool), QueryParseError> {
+    if chars.get(*pos) == Some(&'"') {
+        let quote = *pos;
+        let end = chars[quote + 1..]
+            .iter()
+            .position(|&c| c == '"')
+            .map(|len| quote + 1 + len)
+            .ok_or(QueryParseError {
+                column: quote + 1,
+                kind: QueryErrorKind::UnterminatedQuote,
+            })?;
+        *pos = end + 1;
+        return Ok((chars[quote + 1..end].iter().collect(), true));
+    }
+
+    let start = *pos;
+    while *pos < chars.len() && !chars[*pos].is_whitespace() && !(is_key && chars[*pos] == ':') {
+        *pos += 1;
+    }
+    Ok((chars[start..*pos].iter().collect(), false))
+}
+
+#[derive(Default)]
+struct QueryBuilder {
+    request: PocketGetRequest<'static>,
+    search: Vec<String>,
+    /// Single-valued filters already set.
+    filters: HashSet<&'static str>,
+    unsupported: Vec<UnsupportedTerm>,
+}
+
+impl QueryBuilder {
+    fn apply(&mut self, term: Term, now: DateTime<Utc>) -> Result<(), QueryParseError> {
+        let key = match term.key.clone() {
+            Some(key) => key,
+            None if term.negated => return self.skip(term, "search cannot exclude words"),
+            None => {
+                self.search.push(term.value);
+                return Ok(());
+            }
+        };
+
+        if term.negated && !(key == "is" && is_favorite(&term.value)) {
+            return self.skip(term, "only is:fav can be negated");
+        }
+
+        match key.as_str() {
+            "tag" => {
+                self.set_once("tag", &term)?;
+                if term.value == UNTAGGED {
+                    self.request.tag(PocketGetTag::Untagged);
+                } else {
+                    self.request.tag(PocketGetTag::tagged(term.value.clone()));
+                }
+            }
+            "is" if is_favorite(&term.value) => {
+                self.set_once("favorite", &term)?;
+                self.request.favorite(!term.negated);
+            }
+            "is" if term.value.eq_ignore_ascii_case("untagged") => {
+                self.set_once("tag", &term)?;
+                self.request.tag(PocketGetTag::Untagged);
+            }
+            "is" => {
+                let state = match term.value.to_lowercase().as_str() {
+                    "unread" => PocketGetState::Unread,
+                    "archived" | "archive" => PocketGetState::Archive,
+                    "all" => PocketGetState::All,
+                    _ => return Err(term.invalid()),
+                };
+                self.set_once("state", &term)?;
+                self.request.state(state);
+            }
+            "type" => {
+                let content_type = match term.value.to_lowercase().as_str() {
+                    "article" => PocketGetType::Article,
+                    "video" => PocketGetType::Video,
+                    "image" => PocketGetType::Image,
+                    _ => return Err(term.invalid()),
+                };
+                self.set_once("type", &term)?;
+                self.request.content_type(content_type);
+            }
+            "domain" => {
+                self.set_once("domain", &term)?;
+                self.request.domain(term.value.clone());
+            }
+            "since" => {
+                let since = parse_since(&term.value, now).ok_or_else(|| term.invalid())?;
+                self.set_once("since", &term)?;
+                self.request.since(since);
+            }
+            "sort" => {
+                let sort = match term.value.to_lowercase().as_str() {
+                    "newest" => PocketGetSort::Newest,
+                    "oldest" => PocketGetSort::Oldest,
+                    "title" => PocketGetSort::Title,
+                    "site" => PocketGetSort::Site,
+                    _ => return Err(term.invalid()),
+                };
+                self.set_once("sort", &term)?;
+                self.request.sort(sort);
+            }
+            "detail" => {
+                let detail = match term.value.to_lowercase().as_str() {
+                    "simple" => PocketGetDetail::Simple,
+                    "complete" => PocketGetDetail::Complete,
+                    _ => return Err(term.invalid()),
+                };
+                self.set_once("detail", &term)?;
+                self.request.detail_type(detail);
+            }
+            "limit" | "count" => {
+                let count = term.value.parse().map_err(|_| term.invalid())?;
+                self.set_once("limit", &term)?;
+                self.request.count(count);
+            }
+            "offset" => {
+                let offset = term.value.parse().map_err(|_| term.invalid())?;
+                self.set_once("offset", &term)?;
+                self.request.offset(offset);
+            }
+            _ => return self.skip(term, "unknown filter"),
+        }
+
+        Ok(())
+    }
+
+    fn set_once(&mut self, filter: &'static str, term: &Term) -> Result<(), QueryParseError> {
+        if self.filters.insert(filter) {
+            Ok(())
+        } else {
+            Err(QueryParseError {
+                column: term.column,
+                kind: QueryErrorKind::DuplicateFilter { filter },
+            })
+        }
+    }
+
+    fn skip(&mut self, term: Term, reason: &'static str) -> Result<(), QueryParseError> {
+        self.unsupported.push(term.unsupported(reason));
+        Ok(())
+    }
+
+    fn finish(mut self) -> ParsedQuery {
+        if !self.search.is_empty() {
+            self.request.search(self.search.join(" "));
+        }
+        ParsedQuery {
+            request: self.request,
+            unsupported: self.unsupported,
+        }
+    }
+}
+
+fn is_favorite(value: &str) -> bool {
+    matches!(
+        value.to_lowercase().as_str(),
+        "fav" | "favorite" | "favorited" | "starred"
+    )
+}
+
+fn parse_since(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return date.and_hms_opt(0, 0, 0).map(|date| date.and_utc());
+    }
+
+    let split = value.len().checked_sub(1)?;
+    if !value.is_char_boundary(split) {
+        return None;
+    }
+    let (amount, unit) = value.split_at(split);
+    let amount = amount.parse::<i64>().ok().filter(|&amount| amount > 0)?;
+    let ago = match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    }?;
+    now.checked_sub_signed(ago)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.timestamp_opt(1584221353, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_full_query() {
+        let query = ParsedQuery::parse_at(
+            r#"tag:rust is:unread is:fav type:video domain:github.com since:7d sort:oldest limit:50 "search words""#,
+            now(),
+        )
+        .unwrap();
+
+        let mut expected = PocketGetRequest::new();
+        expected
+            .tag(PocketGetTag::tagged("rust"))
+            .unread()
+            .favorite(true)
+            .videos()
+            .domain("github.com")
+            .since(now() - Duration::try_days(7).unwrap())
+            .sort_by_oldest()
+            .count(50)
+            .search("search words");
+        assert_eq!(query.request, expected);
+        assert!(query.unsupported.is_empty());
+    }
+
+    #[test]
+    fn test_parse_search_words_and_dates() {
+        let query = ParsedQuery::parse_at("async since:2020-03-14 rust -is:fav", now()).unwrap();
+
+        let mut expected = PocketGetRequest::new();
+        expected
+            .search("async rust")
+            .since(Utc.with_ymd_and_hms(2020, 3, 14, 0, 0, 0).unwrap())
+            .favorite(false);
+        assert_eq!(query.request, expected);
+    }
+
+    #[test]
+    fn test_parse_lists_unsupported_terms() {
+        let query =
+            ParsedQuery::parse_at("tag:rust -async lang:en -tag:go -is:unread", now()).unwrap();
+
+        let unsupported = query
+            .unsupported
+            .iter()
+            .map(|term| (term.column, term.term.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            unsupported,
+            vec![
+                (10, "-async"),
+                (17, "lang:en"),
+                (25, "-tag:go"),
+                (33, "-is:unread")
+            ]
+        );
+        assert_eq!(query.unsupported[1].reason, "unknown filter");
+    }
+
+    #[test]
+    fn test_parse_rejects_duplicate_filters() {
+        let duplicate = |input: &str| {
+            let error = ParsedQuery::parse_at(input, now()).unwrap_err();
+            match error.kind {
+                QueryErrorKind::DuplicateFilter { filter } => (error.column, filter),
+                kind => panic!("unexpected error: {:?}", kind),
+            }
+        };
+
+        assert_eq!(duplicate("tag:rust tag:async"), (10, "tag"));
+        assert_eq!(duplicate("tag:rust is:untagged"), (10, "tag"));
+        assert_eq!(duplicate("is:unread is:archived"), (11, "state"));
+        assert_eq!(duplicate("is:fav -is:fav"), (8, "favorite"));
+        assert_eq!(duplicate("domain:a.com domain:b.com"), (14, "domain"));
+        assert_eq!(duplicate("since:1d since:2020-03-14"), (10, "since"));
+        assert_eq!(duplicate("sort:oldest sort:newest"), (13, "sort"));
+        assert_eq!(duplicate("limit:10 count:20"), (10, "limit"));
+        assert_eq!(
+            ParsedQuery::parse_at("type:video type:video", now())
+                .unwrap_err()
+                .to_string(),
+            "more than one type filter at column 12"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_non_positive_since() {
+        for since in &["since:-5d", "since:0h"] {
+            assert!(matches!(
+                ParsedQuery::parse_at(since, now()),
+                Err(QueryParseError {
+                    column: 1,
+                    kind: QueryErrorKind::InvalidValue { .. },
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_errors_carry_columns() {
+        assert_eq!(
+            ParsedQuery::parse_at("is:unread type:podcast", now()),
+            Err(QueryParseError {
+                column: 11,
+                kind: QueryErrorKind::InvalidValue {
+                    key: "type".to_string(),
+                    value: "podcast".to_string()
+                },
+            })
+        );
+        assert_eq!(
+            ParsedQuery::parse_at("rust \"open quote", now())
+                .unwrap_err()
+                .column,
+            6
+        );
+        assert_eq!(
+            ParsedQuery::parse_at("limit:", now())
+                .unwrap_err()
+                .to_string(),
+            "missing value for \"limit\" at column 1"
+        );
+        assert_eq!(
+            ParsedQuery::parse_at("since:yesterday", now())
+                .unwrap_err()
+                .column,
+            1
+        );
+    }
+}
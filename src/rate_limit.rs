@@ -0,0 +1,304 @@
+use crate::headers::{
+    HEADER_XLIMIT_KEY_LIMIT, HEADER_XLIMIT_KEY_REMAINING, HEADER_XLIMIT_KEY_RESET,
+    HEADER_XLIMIT_USER_LIMIT, HEADER_XLIMIT_USER_REMAINING, HEADER_XLIMIT_USER_RESET,
+};
+use hyper::HeaderMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::time::delay_for;
+
+/// How long `PocketBuilder::throttle` waits at most for a rate-limit window to reset.
+pub const DEFAULT_MAX_THROTTLE_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// One rate-limit window as reported by the `X-Limit-*` headers.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RateLimit {
+    /// Calls allowed per window.
+    pub limit: u32,
+    /// Calls left in the current window.
+    pub remaining: u32,
+    /// Time until the window resets, counted from `RateLimitStatus::received_at`.
+    pub reset: Duration,
+}
+
+/// Rate-limit quota reported with a response, for both the user and the consumer key.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RateLimitStatus {
+    pub user: Option<RateLimit>,
+    pub key: Option<RateLimit>,
+    pub received_at: Instant,
+}
+
+impl RateLimitStatus {
+    pub fn from_headers(headers: &HeaderMap) -> Option<RateLimitStatus> {
+        let user = RateLimit::from_headers(
+            headers,
+            HEADER_XLIMIT_USER_LIMIT,
+            HEADER_XLIMIT_USER_REMAINING,
+            HEADER_XLIMIT_USER_RESET,
+        );
+        let key = RateLimit::from_headers(
+            headers,
+            HEADER_XLIMIT_KEY_LIMIT,
+            HEADER_XLIMIT_KEY_REMAINING,
+            HEADER_XLIMIT_KEY_RESET,
+        );
+
+        if user.is_none() && key.is_none() {
+            return None;
+        }
+
+        Some(RateLimitStatus {
+            user,
+            key,
+            received_at: Instant::now(),
+        })
+    }
+
+    /// `None` if there is no user quota or its reset is too far away to represent.
+    pub fn user_resets_at(&self) -> Option<Instant> {
+        self.user
+            .and_then(|limit| self.received_at.checked_add(limit.reset))
+    }
+
+    /// `None` if there is no key quota or its reset is too far away to represent.
+    pub fn key_resets_at(&self) -> Option<Instant> {
+        self.key
+            .and_then(|limit| self.received_at.checked_add(limit.reset))
+    }
+
+    /// Returns the instant to wait for when the user or key quota has dropped below
+    /// `min_remaining` and its window has not reset yet.
+    pub fn throttled_until(&self, min_remaining: u32) -> Option<Instant> {
+        let user = self
+            .user
+            .filter(|limit| limit.remaining < min_remaining)
+            .and_then(|_| self.user_resets_at());
+        let key = self
+            .key
+            .filter(|limit| limit.remaining < min_remaining)
+            .and_then(|_| self.key_resets_at());
+
+        user.into_iter()
+            .chain(key)
+            .max()
+            .filter(|instant| *instant > Instant::now())
+    }
+}
+
+/// A token bucket allowing `capacity` calls per `period`.
+///
+/// Share one bucket between several `Pocket` instances with an `Arc` to keep them
+/// all under a key-wide limit.
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    period: Duration,
+    state: Mutex<TokenBucketState>,
+}
+
+#[derive(Debug)]
+struct TokenBucketState {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, period: Duration) -> TokenBucket {
+        assert!(capacity > 0, "token bucket capacity must be positive");
+        TokenBucket {
+            capacity: f64::from(capacity),
+            period,
+            state: Mutex::new(TokenBucketState {
+                tokens: f64::from(capacity),
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token if one is available, otherwise returns how long until one is.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let per_token = self.period.as_secs_f64() / self.capacity;
+        if per_token > 0.0 {
+            let refilled = now.duration_since(state.refilled_at).as_secs_f64() / per_token;
+            state.tokens = (state.tokens + refilled).min(self.capacity);
+        } else {
+            state.tokens = self.capacity;
+        }
+        state.refilled_at = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - state.tokens) * per_token))
+        }
+    }
+
+    /// Waits until a token is available and takes it.
+    pub async fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            delay_for(wait).await;
+        }
+    }
+}
+
+impl RateLimit {
+    fn from_headers(
+        headers: &HeaderMap,
+        limit: &str,
+        remaining: &str,
+        reset: &str,
+    ) -> Option<RateLimit> {
+        Some(RateLimit {
+            limit: parse_header(headers, limit)?,
+            remaining: parse_header(headers, remaining)?,
+            reset: Duration::from_secs(parse_header(headers, reset)?),
+        })
+    }
+}
+
+fn parse_header<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_rate_limit_status_from_headers() {
+        let headers = headers(&[
+            (HEADER_XLIMIT_USER_LIMIT, "320"),
+            (HEADER_XLIMIT_USER_REMAINING, "310"),
+            (HEADER_XLIMIT_USER_RESET, "3000"),
+            (HEADER_XLIMIT_KEY_LIMIT, "10000"),
+            (HEADER_XLIMIT_KEY_REMAINING, "9990"),
+            (HEADER_XLIMIT_KEY_RESET, "80000"),
+        ]);
+
+        let actual = RateLimitStatus::from_headers(&headers).unwrap();
+
+        assert_eq!(
+            actual.user,
+            Some(RateLimit {
+                limit: 320,
+                remaining: 310,
+                reset: Duration::from_secs(3000),
+            })
+        );
+        assert_eq!(
+            actual.key,
+            Some(RateLimit {
+                limit: 10000,
+                remaining: 9990,
+                reset: Duration::from_secs(80000),
+            })
+        );
+    }
+
+    #[test]
+    fn test_rate_limit_status_from_partial_headers() {
+        let headers = headers(&[
+            (HEADER_XLIMIT_USER_LIMIT, "320"),
+            (HEADER_XLIMIT_USER_REMAINING, "not a number"),
+            (HEADER_XLIMIT_USER_RESET, "3000"),
+            (HEADER_XLIMIT_KEY_LIMIT, "10000"),
+            (HEADER_XLIMIT_KEY_REMAINING, "9990"),
+            (HEADER_XLIMIT_KEY_RESET, "80000"),
+        ]);
+
+        let actual = RateLimitStatus::from_headers(&headers).unwrap();
+
+        assert_eq!(actual.user, None);
+        assert!(actual.key.is_some());
+    }
+
+    #[test]
+    fn test_throttled_until() {
+        let status = RateLimitStatus {
+            user: Some(RateLimit {
+                limit: 320,
+                remaining: 5,
+                reset: Duration::from_secs(60),
+            }),
+            key: Some(RateLimit {
+                limit: 10000,
+                remaining: 9000,
+                reset: Duration::from_secs(600),
+            }),
+            received_at: Instant::now(),
+        };
+
+        assert_eq!(status.throttled_until(5), None);
+        assert_eq!(status.throttled_until(6), status.user_resets_at());
+        assert_eq!(status.throttled_until(10000), status.key_resets_at());
+    }
+
+    #[test]
+    fn test_throttled_until_after_reset() {
+        let status = RateLimitStatus {
+            user: Some(RateLimit {
+                limit: 320,
+                remaining: 0,
+                reset: Duration::from_secs(0),
+            }),
+            key: None,
+            received_at: Instant::now(),
+        };
+
+        assert_eq!(status.throttled_until(1), None);
+    }
+
+    #[test]
+    fn test_unrepresentable_reset_does_not_panic() {
+        let headers = headers(&[
+            (HEADER_XLIMIT_USER_LIMIT, "320"),
+            (HEADER_XLIMIT_USER_REMAINING, "0"),
+            (HEADER_XLIMIT_USER_RESET, "18446744073709551615"),
+        ]);
+
+        let status = RateLimitStatus::from_headers(&headers).unwrap();
+
+        assert_eq!(status.user_resets_at(), None);
+        assert_eq!(status.throttled_until(0), None);
+        assert_eq!(status.throttled_until(1), None);
+    }
+
+    #[test]
+    fn test_token_bucket_try_acquire() {
+        let bucket = TokenBucket::new(2, Duration::from_secs(60));
+
+        assert_eq!(bucket.try_acquire(), Ok(()));
+        assert_eq!(bucket.try_acquire(), Ok(()));
+        let wait = bucket.try_acquire().unwrap_err();
+        assert!(wait > Duration::from_secs(29) && wait <= Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_token_bucket_acquire_waits_for_refill() {
+        let bucket = TokenBucket::new(1, Duration::from_millis(50));
+        let started = Instant::now();
+
+        bucket.acquire().await;
+        bucket.acquire().await;
+
+        assert!(started.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[test]
+    fn test_rate_limit_status_from_no_headers() {
+        assert_eq!(RateLimitStatus::from_headers(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,181 @@
+use chrono::{DateTime, Utc};
+use hyper::header::RETRY_AFTER;
+use hyper::HeaderMap;
+use rand::Rng;
+use std::time::Duration;
+
+/// How `Pocket` retries calls that fail with a connection error or a 5xx / 429 response.
+///
+/// Only idempotent calls (`get`) are retried unless `retry_non_idempotent` is set,
+/// which opts `add`, `send` and the OAuth calls in as well.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for every following one.
+    pub base_delay: Duration,
+    /// Upper bound for the computed backoff delay and for the server's `Retry-After`.
+    pub max_delay: Duration,
+    /// Randomize every delay between half and all of its computed value.
+    pub jitter: bool,
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn allows_retry(&self, attempt: u32, idempotent: bool) -> bool {
+        attempt < self.max_attempts && (idempotent || self.retry_non_idempotent)
+    }
+
+    /// Delay to wait after the given (1-based) failed attempt.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self
+            .base_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay));
+
+        if self.jitter && delay > Duration::from_millis(0) {
+            rand::thread_rng().gen_range(delay / 2, delay)
+        } else {
+            delay
+        }
+    }
+
+    /// Delay to wait after the given failed attempt, honoring the server's
+    /// `Retry-After` up to `max_delay`.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        match retry_after {
+            Some(retry_after) => retry_after.min(self.max_delay),
+            None => self.backoff(attempt),
+        }
+    }
+}
+
+/// Parses a `Retry-After` header given either in seconds or as an HTTP date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    DateTime::parse_from_rfc2822(value).ok().map(|date| {
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    #[test]
+    fn test_backoff_without_jitter() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            jitter: false,
+            retry_non_idempotent: false,
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(64), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_backoff_with_jitter() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            ..Default::default()
+        };
+
+        for _ in 0..100 {
+            let delay = policy.backoff(2);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn test_allows_retry() {
+        let policy = RetryPolicy::default();
+
+        assert!(policy.allows_retry(1, true));
+        assert!(!policy.allows_retry(3, true));
+        assert!(!policy.allows_retry(1, false));
+        assert!(RetryPolicy {
+            retry_non_idempotent: true,
+            ..Default::default()
+        }
+        .allows_retry(1, false));
+        assert!(!RetryPolicy::none().allows_retry(1, true));
+    }
+
+    #[test]
+    fn test_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn test_retry_after_date_in_the_past() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn test_retry_after_is_capped_at_max_delay() {
+        let policy = RetryPolicy {
+            max_delay: Duration::from_secs(30),
+            jitter: false,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(86400))),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(2))),
+            Duration::from_secs(2)
+        );
+        assert_eq!(policy.delay(1, None), policy.base_delay);
+    }
+
+    #[test]
+    fn test_retry_after_missing() {
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+}
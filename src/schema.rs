@@ -0,0 +1,247 @@
+//! Detection of response fields this crate does not know about yet.
+//!
+//! Every response type has an `extra` map for the fields it does not declare. It is
+//! only filled with the `extra-fields` feature, and left empty otherwise, so
+//! enabling the feature doesn't change any type.
+//! `UnknownFields::unknown_fields` summarizes them for a whole response:
+//!
+//! ```ignore
+//! let response = pocket.get_response(&pocket.filter()).await?;
+//! for (path, count) in response.unknown_fields() {
+//!     println!("{} appeared {} times", path, count);
+//! }
+//! ```
+
+use crate::add::{PocketAddResponse, PocketAddedItem};
+use crate::get::{
+    DomainMetaData, ItemAnnotation, ItemImage, ItemTag, LenientList, PocketGetResponse, PocketItem,
+    PocketSearchMeta,
+};
+use crate::send::{PocketSendResponse, SendActionError, SendActionResult};
+use crate::{ItemAuthor, ItemVideo, PocketImage};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+pub type ExtraFields = BTreeMap<String, Value>;
+
+pub trait UnknownFields {
+    /// Adds the unknown keys of `self` and of everything it contains to `counts`,
+    /// keyed by their dotted path below `prefix`.
+    fn collect_unknown_fields(&self, prefix: &str, counts: &mut BTreeMap<String, usize>);
+
+    /// Unknown keys by dotted path (e.g. `list.images.new_field`), with the number
+    /// of objects each one appeared in.
+    fn unknown_fields(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        self.collect_unknown_fields("", &mut counts);
+        counts
+    }
+}
+
+fn count_extra(extra: &ExtraFields, prefix: &str, counts: &mut BTreeMap<String, usize>) {
+    for key in extra.keys() {
+        *counts.entry(join(prefix, key)).or_insert(0) += 1;
+    }
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+impl<T: UnknownFields> UnknownFields for [T] {
+    fn collect_unknown_fields(&self, prefix: &str, counts: &mut BTreeMap<String, usize>) {
+        for value in self {
+            value.collect_unknown_fields(prefix, counts);
+        }
+    }
+}
+
+impl<T: UnknownFields> UnknownFields for Option<T> {
+    fn collect_unknown_fields(&self, prefix: &str, counts: &mut BTreeMap<String, usize>) {
+        if let Some(value) = self {
+            value.collect_unknown_fields(prefix, counts);
+        }
+    }
+}
+
+impl<T: UnknownFields> UnknownFields for Vec<T> {
+    fn collect_unknown_fields(&self, prefix: &str, counts: &mut BTreeMap<String, usize>) {
+        self.as_slice().collect_unknown_fields(prefix, counts);
+    }
+}
+
+impl<L: UnknownFields> UnknownFields for PocketGetResponse<L> {
+    fn collect_unknown_fields(&self, prefix: &str, counts: &mut BTreeMap<String, usize>) {
+        count_extra(&self.extra, prefix, counts);
+        self.list
+            .collect_unknown_fields(&join(prefix, "list"), counts);
+        self.search_meta
+            .collect_unknown_fields(&join(prefix, "search_meta"), counts);
+    }
+}
+
+impl<T: UnknownFields> UnknownFields for LenientList<T> {
+    fn collect_unknown_fields(&self, prefix: &str, counts: &mut BTreeMap<String, usize>) {
+        self.items.collect_unknown_fields(prefix, counts);
+    }
+}
+
+impl UnknownFields for PocketItem {
+    fn collect_unknown_fields(&self, prefix: &str, counts: &mut BTreeMap<String, usize>) {
+        count_extra(&self.extra, prefix, counts);
+        self.tags
+            .collect_unknown_fields(&join(prefix, "tags"), counts);
+        self.images
+            .collect_unknown_fields(&join(prefix, "images"), counts);
+        self.videos
+            .collect_unknown_fields(&join(prefix, "videos"), counts);
+        self.authors
+            .collect_unknown_fields(&join(prefix, "authors"), counts);
+        self.domain_metadata
+            .collect_unknown_fields(&join(prefix, "domain_metadata"), counts);
+        self.image
+            .collect_unknown_fields(&join(prefix, "image"), counts);
+        self.annotations
+            .collect_unknown_fields(&join(prefix, "annotations"), counts);
+    }
+}
+
+impl UnknownFields for PocketAddResponse {
+    fn collect_unknown_fields(&self, prefix: &str, counts: &mut BTreeMap<String, usize>) {
+        count_extra(&self.extra, prefix, counts);
+        self.item
+            .collect_unknown_fields(&join(prefix, "item"), counts);
+    }
+}
+
+impl UnknownFields for PocketAddedItem {
+    fn collect_unknown_fields(&self, prefix: &str, counts: &mut BTreeMap<String, usize>) {
+        count_extra(&self.extra, prefix, counts);
+        self.images
+            .collect_unknown_fields(&join(prefix, "images"), counts);
+        self.videos
+            .collect_unknown_fields(&join(prefix, "videos"), counts);
+        self.authors
+            .collect_unknown_fields(&join(prefix, "authors"), counts);
+    }
+}
+
+impl UnknownFields for PocketSendResponse {
+    fn collect_unknown_fields(&self, prefix: &str, counts: &mut BTreeMap<String, usize>) {
+        count_extra(&self.extra, prefix, counts);
+        for result in &self.action_results {
+            if let SendActionResult::Add(item) = result {
+                item.collect_unknown_fields(&join(prefix, "action_results"), counts);
+            }
+        }
+        for error in &self.action_errors {
+            error.collect_unknown_fields(&join(prefix, "action_errors"), counts);
+        }
+    }
+}
+
+macro_rules! leaf_unknown_fields {
+    ($($ty:ty),*) => {
+        $(
+            impl UnknownFields for $ty {
+                fn collect_unknown_fields(&self, prefix: &str, counts: &mut BTreeMap<String, usize>) {
+                    count_extra(&self.extra, prefix, counts);
+                }
+            }
+        )*
+    };
+}
+
+leaf_unknown_fields!(
+    PocketSearchMeta,
+    ItemTag,
+    ItemAnnotation,
+    ItemImage,
+    SendActionError,
+    DomainMetaData,
+    PocketImage,
+    ItemVideo,
+    ItemAuthor
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "extra-fields")]
+    fn test_get_response_unknown_fields() {
+        use crate::utils::test_item::TestItem;
+        use serde_json::json;
+
+        let response = format!(
+            r#"{{
+                "status": 1, "complete": 1, "error": null, "since": 1584221353,
+                "search_meta": {{"search_type": "normal", "engine": "v2"}},
+                "maxActions": 30,
+                "list": {{"1": {}, "2": {}}}
+            }}"#,
+            TestItem::new(1).with(json!({
+                "listen_duration_estimate": 10,
+                "is_syndicated": true,
+                "tags": {"rust": {"item_id": "1", "tag": "rust", "color": "red"}},
+            })),
+            TestItem::new(2).with(json!({"sort_id": 1, "is_syndicated": false})),
+        );
+
+        let response: PocketGetResponse = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(response.list[0].extra["is_syndicated"], Value::Bool(true));
+        let expected = vec![
+            ("list.is_syndicated", 2),
+            ("list.tags.color", 1),
+            ("maxActions", 1),
+            ("search_meta.engine", 1),
+        ];
+        assert_eq!(
+            response.unknown_fields(),
+            expected
+                .into_iter()
+                .map(|(path, count)| (path.to_string(), count))
+                .collect::<BTreeMap<_, _>>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "extra-fields")]
+    fn test_send_response_unknown_fields() {
+        let response = r#"{
+            "status": 1,
+            "action_results": [true, false],
+            "action_errors": [null, {"code": 422, "message": "Invalid", "type": "Unprocessable Entity", "retryable": false}],
+            "request_id": "abc"
+        }"#;
+
+        let response: PocketSendResponse = serde_json::from_str(response).unwrap();
+
+        assert_eq!(response.extra["request_id"], "abc");
+        let expected = vec![("action_errors.retryable", 1), ("request_id", 1)];
+        assert_eq!(
+            response.unknown_fields(),
+            expected
+                .into_iter()
+                .map(|(path, count)| (path.to_string(), count))
+                .collect::<BTreeMap<_, _>>()
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "extra-fields"))]
+    fn test_extra_stays_empty_without_feature() {
+        let response = r#"{"status": 1, "action_results": [true], "action_errors": [null], "request_id": "abc"}"#;
+
+        let response: PocketSendResponse = serde_json::from_str(response).unwrap();
+
+        assert!(response.extra.is_empty());
+        assert!(response.unknown_fields().is_empty());
+    }
+}
@@ -1,5 +1,12 @@
-use crate::{add::PocketAddedItem, serialization::*};
-use serde::{Deserialize, Serialize};
+use crate::errors::PocketError;
+use crate::schema::ExtraFields;
+use crate::{add::PocketAddedItem, serialization::*, PocketResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
 use url::Url;
 
 #[derive(Serialize)]
@@ -7,95 +14,538 @@ pub struct PocketSendRequest<'a> {
     pub actions: &'a [&'a PocketSendAction],
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[serde(tag = "action", rename_all = "snake_case")]
 pub enum PocketSendAction {
     Add {
-        #[serde(serialize_with = "optional_to_string")]
+        #[serde(
+            default,
+            serialize_with = "optional_to_string",
+            deserialize_with = "option_from_str_or_int"
+        )]
         item_id: Option<u64>,
+        #[serde(default)]
         ref_id: Option<String>,
-        tags: Option<String>,
-        #[serde(serialize_with = "optional_to_string")]
-        time: Option<u64>,
+        #[serde(default)]
+        tags: Option<Tags>,
+        #[serde(
+            default,
+            serialize_with = "optional_datetime_to_int",
+            deserialize_with = "option_string_date_unix_timestamp_format"
+        )]
+        time: Option<DateTime<Utc>>,
+        #[serde(default)]
         title: Option<String>,
-        #[serde(with = "url_serde")]
+        #[serde(default, with = "url_serde")]
         url: Option<Url>,
     },
     Archive {
-        #[serde(serialize_with = "to_string")]
+        #[serde(serialize_with = "to_string", deserialize_with = "from_str_or_int")]
         item_id: u64,
-        #[serde(serialize_with = "optional_to_string")]
-        time: Option<u64>,
+        #[serde(
+            default,
+            serialize_with = "optional_datetime_to_int",
+            deserialize_with = "option_string_date_unix_timestamp_format"
+        )]
+        time: Option<DateTime<Utc>>,
     },
     Readd {
-        #[serde(serialize_with = "to_string")]
+        #[serde(serialize_with = "to_string", deserialize_with = "from_str_or_int")]
         item_id: u64,
-        #[serde(serialize_with = "optional_to_string")]
-        time: Option<u64>,
+        #[serde(
+            default,
+            serialize_with = "optional_datetime_to_int",
+            deserialize_with = "option_string_date_unix_timestamp_format"
+        )]
+        time: Option<DateTime<Utc>>,
     },
     Favorite {
-        #[serde(serialize_with = "to_string")]
+        #[serde(serialize_with = "to_string", deserialize_with = "from_str_or_int")]
         item_id: u64,
-        #[serde(serialize_with = "optional_to_string")]
-        time: Option<u64>,
+        #[serde(
+            default,
+            serialize_with = "optional_datetime_to_int",
+            deserialize_with = "option_string_date_unix_timestamp_format"
+        )]
+        time: Option<DateTime<Utc>>,
     },
     Unfavorite {
-        #[serde(serialize_with = "to_string")]
+        #[serde(serialize_with = "to_string", deserialize_with = "from_str_or_int")]
         item_id: u64,
-        #[serde(serialize_with = "optional_to_string")]
-        time: Option<u64>,
+        #[serde(
+            default,
+            serialize_with = "optional_datetime_to_int",
+            deserialize_with = "option_string_date_unix_timestamp_format"
+        )]
+        time: Option<DateTime<Utc>>,
     },
     Delete {
-        #[serde(serialize_with = "to_string")]
+        #[serde(serialize_with = "to_string", deserialize_with = "from_str_or_int")]
         item_id: u64,
-        #[serde(serialize_with = "optional_to_string")]
-        time: Option<u64>,
+        #[serde(
+            default,
+            serialize_with = "optional_datetime_to_int",
+            deserialize_with = "option_string_date_unix_timestamp_format"
+        )]
+        time: Option<DateTime<Utc>>,
     },
     TagsAdd {
-        #[serde(serialize_with = "to_string")]
+        #[serde(serialize_with = "to_string", deserialize_with = "from_str_or_int")]
         item_id: u64,
-        tags: String,
-        #[serde(serialize_with = "optional_to_string")]
-        time: Option<u64>,
+        tags: Tags,
+        #[serde(
+            default,
+            serialize_with = "optional_datetime_to_int",
+            deserialize_with = "option_string_date_unix_timestamp_format"
+        )]
+        time: Option<DateTime<Utc>>,
     },
     TagsRemove {
-        #[serde(serialize_with = "to_string")]
+        #[serde(serialize_with = "to_string", deserialize_with = "from_str_or_int")]
         item_id: u64,
-        tags: String,
-        #[serde(serialize_with = "optional_to_string")]
-        time: Option<u64>,
+        tags: Tags,
+        #[serde(
+            default,
+            serialize_with = "optional_datetime_to_int",
+            deserialize_with = "option_string_date_unix_timestamp_format"
+        )]
+        time: Option<DateTime<Utc>>,
     },
     TagsReplace {
-        #[serde(serialize_with = "to_string")]
+        #[serde(serialize_with = "to_string", deserialize_with = "from_str_or_int")]
         item_id: u64,
-        tags: String,
-        #[serde(serialize_with = "optional_to_string")]
-        time: Option<u64>,
+        tags: Tags,
+        #[serde(
+            default,
+            serialize_with = "optional_datetime_to_int",
+            deserialize_with = "option_string_date_unix_timestamp_format"
+        )]
+        time: Option<DateTime<Utc>>,
     },
     TagsClear {
-        #[serde(serialize_with = "to_string")]
+        #[serde(serialize_with = "to_string", deserialize_with = "from_str_or_int")]
         item_id: u64,
-        #[serde(serialize_with = "optional_to_string")]
-        time: Option<u64>,
+        #[serde(
+            default,
+            serialize_with = "optional_datetime_to_int",
+            deserialize_with = "option_string_date_unix_timestamp_format"
+        )]
+        time: Option<DateTime<Utc>>,
     },
     TagRename {
-        old_tag: String,
-        new_tag: String,
-        #[serde(serialize_with = "optional_to_string")]
-        time: Option<u64>,
+        old_tag: Tag,
+        new_tag: Tag,
+        #[serde(
+            default,
+            serialize_with = "optional_datetime_to_int",
+            deserialize_with = "option_string_date_unix_timestamp_format"
+        )]
+        time: Option<DateTime<Utc>>,
     },
     TagDelete {
-        tag: String,
-        #[serde(serialize_with = "optional_to_string")]
-        time: Option<u64>,
+        tag: Tag,
+        #[serde(
+            default,
+            serialize_with = "optional_datetime_to_int",
+            deserialize_with = "option_string_date_unix_timestamp_format"
+        )]
+        time: Option<DateTime<Utc>>,
     },
 }
 
+/// A list of tags, sent to Pocket as one comma-separated string.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Tags(Vec<String>);
+
+impl Tags {
+    /// Trims every tag. Fails if there are no tags, or if a tag is empty or
+    /// contains a comma.
+    pub fn new<I, S>(tags: I) -> Result<Tags, TagError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let tags = tags
+            .into_iter()
+            .map(|tag| Tag::new(tag.as_ref()).map(|tag| tag.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        if tags.is_empty() {
+            Err(TagError::NoTags)
+        } else {
+            Ok(Tags(tags))
+        }
+    }
+
+    pub fn as_slice(&self) -> &[String] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromStr for Tags {
+    type Err = TagError;
+
+    /// Parses comma-separated tags, e.g. `rust, async`.
+    fn from_str(s: &str) -> Result<Tags, TagError> {
+        if s.trim().is_empty() {
+            Err(TagError::NoTags)
+        } else {
+            Tags::new(s.split(','))
+        }
+    }
+}
+
+impl fmt::Display for Tags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.join(","))
+    }
+}
+
+impl Serialize for Tags {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Tags {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Tags, D::Error> {
+        from_str(deserializer)
+    }
+}
+
+/// A single tag, e.g. the one renamed by `PocketSendAction::TagRename`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Tag(String);
+
+impl Tag {
+    /// Trims `tag`. Fails if it is empty or contains a comma.
+    pub fn new(tag: &str) -> Result<Tag, TagError> {
+        match tag.trim() {
+            "" => Err(TagError::Empty),
+            tag if tag.contains(',') => Err(TagError::Comma(tag.to_string())),
+            tag => Ok(Tag(tag.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Tag {
+    type Err = TagError;
+
+    fn from_str(s: &str) -> Result<Tag, TagError> {
+        Tag::new(s)
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Tag, D::Error> {
+        from_str(deserializer)
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum TagError {
+    #[error("empty tag")]
+    Empty,
+    #[error("tag {0:?} contains a comma")]
+    Comma(String),
+    #[error("no tags")]
+    NoTags,
+}
+
+/// How `Pocket::send` calls `/v3/send`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum SendMethod {
+    /// A JSON body, like `add` and `get`.
+    #[default]
+    Post,
+    /// The actions in the `actions` query parameter, which proxies and servers may
+    /// reject for long batches.
+    Get,
+}
+
+/// Default number of actions sent per `/v3/send` call by `Pocket::send_batch`.
+pub const DEFAULT_SEND_CHUNK_SIZE: usize = 100;
+
+/// Builds a list of actions for `Pocket::send_batch`, which sends them in chunks
+/// of `chunk_size` actions.
+#[derive(Debug, PartialEq)]
+pub struct SendBatch {
+    actions: Vec<PocketSendAction>,
+    chunk_size: usize,
+}
+
+impl Default for SendBatch {
+    fn default() -> SendBatch {
+        SendBatch::new()
+    }
+}
+
+impl SendBatch {
+    pub fn new() -> SendBatch {
+        SendBatch {
+            actions: vec![],
+            chunk_size: DEFAULT_SEND_CHUNK_SIZE,
+        }
+    }
+
+    /// Panics if `chunk_size` is zero.
+    pub fn chunk_size(&mut self, chunk_size: usize) -> &mut SendBatch {
+        assert!(chunk_size > 0, "chunk_size must be positive");
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    pub fn push(&mut self, action: PocketSendAction) -> &mut SendBatch {
+        self.actions.push(action);
+        self
+    }
+
+    pub fn add(&mut self, url: &Url) -> &mut SendBatch {
+        self.push(PocketSendAction::Add {
+            item_id: None,
+            ref_id: None,
+            tags: None,
+            time: None,
+            title: None,
+            url: Some(url.clone()),
+        })
+    }
+
+    pub fn archive(&mut self, item_id: u64) -> &mut SendBatch {
+        self.push(PocketSendAction::Archive {
+            item_id,
+            time: None,
+        })
+    }
+
+    pub fn readd(&mut self, item_id: u64) -> &mut SendBatch {
+        self.push(PocketSendAction::Readd {
+            item_id,
+            time: None,
+        })
+    }
+
+    pub fn favorite(&mut self, item_id: u64) -> &mut SendBatch {
+        self.push(PocketSendAction::Favorite {
+            item_id,
+            time: None,
+        })
+    }
+
+    pub fn unfavorite(&mut self, item_id: u64) -> &mut SendBatch {
+        self.push(PocketSendAction::Unfavorite {
+            item_id,
+            time: None,
+        })
+    }
+
+    pub fn delete(&mut self, item_id: u64) -> &mut SendBatch {
+        self.push(PocketSendAction::Delete {
+            item_id,
+            time: None,
+        })
+    }
+
+    /// Fails if `tags` are not valid `Tags`.
+    pub fn tags_add<S: AsRef<str>>(
+        &mut self,
+        item_id: u64,
+        tags: &[S],
+    ) -> Result<&mut SendBatch, TagError> {
+        let tags = Tags::new(tags)?;
+        Ok(self.push(PocketSendAction::TagsAdd {
+            item_id,
+            tags,
+            time: None,
+        }))
+    }
+
+    pub fn tags_remove<S: AsRef<str>>(
+        &mut self,
+        item_id: u64,
+        tags: &[S],
+    ) -> Result<&mut SendBatch, TagError> {
+        let tags = Tags::new(tags)?;
+        Ok(self.push(PocketSendAction::TagsRemove {
+            item_id,
+            tags,
+            time: None,
+        }))
+    }
+
+    pub fn tags_replace<S: AsRef<str>>(
+        &mut self,
+        item_id: u64,
+        tags: &[S],
+    ) -> Result<&mut SendBatch, TagError> {
+        let tags = Tags::new(tags)?;
+        Ok(self.push(PocketSendAction::TagsReplace {
+            item_id,
+            tags,
+            time: None,
+        }))
+    }
+
+    pub fn tags_clear(&mut self, item_id: u64) -> &mut SendBatch {
+        self.push(PocketSendAction::TagsClear {
+            item_id,
+            time: None,
+        })
+    }
+
+    pub fn tag_rename(&mut self, old_tag: &str, new_tag: &str) -> Result<&mut SendBatch, TagError> {
+        let (old_tag, new_tag) = (Tag::new(old_tag)?, Tag::new(new_tag)?);
+        Ok(self.push(PocketSendAction::TagRename {
+            old_tag,
+            new_tag,
+            time: None,
+        }))
+    }
+
+    pub fn tag_delete(&mut self, tag: &str) -> Result<&mut SendBatch, TagError> {
+        let tag = Tag::new(tag)?;
+        Ok(self.push(PocketSendAction::TagDelete { tag, time: None }))
+    }
+
+    pub fn actions(&self) -> &[PocketSendAction] {
+        &self.actions
+    }
+
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    pub(crate) fn chunks(&self) -> impl Iterator<Item = Vec<&PocketSendAction>> {
+        self.actions
+            .chunks(self.chunk_size)
+            .map(|chunk| chunk.iter().collect())
+    }
+}
+
 #[derive(Deserialize, Debug, PartialEq)]
 pub struct PocketSendResponse {
     pub status: u16,
     pub action_results: Vec<SendActionResult>,
     pub action_errors: Vec<Option<SendActionError>>,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
+}
+
+impl PocketSendResponse {
+    /// Appends the results of the next chunk of a batch. The merged `status` is
+    /// only 1 if it is 1 for every chunk.
+    pub(crate) fn merge(&mut self, next: PocketSendResponse) {
+        if self.status == 1 {
+            self.status = next.status;
+        }
+        self.action_results.extend(next.action_results);
+        self.action_errors.extend(next.action_errors);
+        self.extra.extend(next.extra);
+    }
+
+    /// Pairs each of the submitted `actions` with its outcome. Fails with
+    /// `PocketError::ResultCountMismatch` if Pocket didn't return exactly one result
+    /// and one error per action.
+    pub fn outcomes<A: Borrow<PocketSendAction>>(
+        self,
+        actions: &[A],
+    ) -> PocketResult<Vec<(&PocketSendAction, ActionOutcome)>> {
+        let expected = actions.len();
+        for actual in [self.action_results.len(), self.action_errors.len()] {
+            if actual != expected {
+                return Err(PocketError::ResultCountMismatch { expected, actual });
+            }
+        }
+
+        let outcomes =
+            self.action_results
+                .into_iter()
+                .zip(self.action_errors)
+                .map(|(result, error)| match result {
+                    SendActionResult::Success => ActionOutcome::Succeeded,
+                    SendActionResult::Add(item) => ActionOutcome::SucceededAdd(item),
+                    SendActionResult::Failure => ActionOutcome::failed(error),
+                });
+        Ok(actions.iter().map(Borrow::borrow).zip(outcomes).collect())
+    }
+
+    /// Like `outcomes`, but fails with `PocketError::ActionsFailed`, holding every
+    /// outcome, if any action failed.
+    pub fn ensure_success<A: Borrow<PocketSendAction>>(
+        self,
+        actions: &[A],
+    ) -> PocketResult<Vec<(&PocketSendAction, ActionOutcome)>> {
+        let outcomes = self.outcomes(actions)?;
+        if outcomes.iter().all(|(_, outcome)| outcome.is_success()) {
+            Ok(outcomes)
+        } else {
+            Err(PocketError::ActionsFailed {
+                outcomes: outcomes.into_iter().map(|(_, outcome)| outcome).collect(),
+            })
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ActionOutcome {
+    Succeeded,
+    SucceededAdd(Box<PocketAddedItem>),
+    /// `code` is 0 if Pocket didn't say why the action failed.
+    Failed {
+        code: u16,
+        message: String,
+        error_type: String,
+    },
+}
+
+impl ActionOutcome {
+    fn failed(error: Option<SendActionError>) -> ActionOutcome {
+        match error {
+            Some(error) => ActionOutcome::Failed {
+                code: error.code,
+                message: error.message,
+                error_type: error.error_type,
+            },
+            None => ActionOutcome::Failed {
+                code: 0,
+                message: "Action failed".to_string(),
+                error_type: String::new(),
+            },
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        !matches!(self, ActionOutcome::Failed { .. })
+    }
 }
 
 #[derive(Deserialize, Debug, PartialEq)]
@@ -108,19 +558,23 @@ pub enum SendActionResult {
     Add(Box<PocketAddedItem>),
 }
 
-#[derive(Deserialize, Debug, PartialEq)]
+#[derive(Deserialize, Debug, PartialEq, Clone)]
 pub struct SendActionError {
-    code: u16,
-    message: String,
+    pub code: u16,
+    pub message: String,
     #[serde(rename = "type")]
-    error_type: String,
+    pub error_type: String,
+    /// Fields unknown to this version of the crate.
+    #[cfg_attr(feature = "extra-fields", serde(flatten))]
+    #[cfg_attr(not(feature = "extra-fields"), serde(skip))]
+    pub extra: ExtraFields,
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::PocketItemHas;
-    use chrono::{TimeZone, Utc};
+    use chrono::{NaiveDateTime, TimeZone};
 
     #[test]
     fn test_deserialize_send_response() {
@@ -133,8 +587,10 @@ mod test {
                     code: 422,
                     message: "Invalid/non-existent URL".to_string(),
                     error_type: "Unprocessable Entity".to_string(),
+                    extra: Default::default(),
                 }),
             ],
+            extra: Default::default(),
         };
         let response = r#"
             {
@@ -154,7 +610,7 @@ mod test {
             }
         "#;
 
-        let actual: PocketSendResponse = serde_json::from_str(&response).unwrap();
+        let actual: PocketSendResponse = serde_json::from_str(response).unwrap();
 
         assert_eq!(actual, expected);
     }
@@ -177,7 +633,7 @@ mod test {
                         mime_type: "text/html".parse().ok(),
                         content_length: 648,
                         encoding: "utf-8".to_string(),
-                        date_resolved: Utc.datetime_from_str("2020-08-04 22:41:28", FORMAT).ok(),
+                        date_resolved: NaiveDateTime::parse_from_str("2020-08-04 22:41:28", FORMAT).ok().map(|d| d.and_utc()),
                         date_published: None,
                         title: "Example Domain".to_string(),
                         excerpt: "This domain is for use in illustrative examples in documents. You may use this domain in literature without prior coordination or asking for permission. More information...".to_string(),
@@ -196,10 +652,12 @@ mod test {
                         videos: Some(vec![]),
                         resolved_normal_url: Url::parse("http://example.com").ok(),
                         given_url: Url::parse("https://example.com/").unwrap(),
+                        extra: Default::default(),
                     })
                 ),
             ],
             action_errors: vec![None],
+            extra: Default::default(),
         };
         let response = r#"
             {
@@ -250,8 +708,211 @@ mod test {
             }
         "#;
 
-        let actual: PocketSendResponse = serde_json::from_str(&response).unwrap();
+        let actual: PocketSendResponse = serde_json::from_str(response).unwrap();
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_send_batch_actions() {
+        let mut batch = SendBatch::new();
+        batch
+            .archive(1)
+            .tags_add(2, &["one", "two"])
+            .unwrap()
+            .tag_rename("one", "1")
+            .unwrap()
+            .chunk_size(2);
+
+        assert_eq!(
+            serde_json::to_value(batch.actions()).unwrap(),
+            serde_json::json!([
+                {"action": "archive", "item_id": "1", "time": null},
+                {"action": "tags_add", "item_id": "2", "tags": "one,two", "time": null},
+                {"action": "tag_rename", "old_tag": "one", "new_tag": "1", "time": null},
+            ])
+        );
+        assert_eq!(
+            batch.chunks().map(|chunk| chunk.len()).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert!(matches!(
+            batch.tags_remove(3, &["a,b"]),
+            Err(TagError::Comma(_))
+        ));
+        assert!(matches!(
+            batch.tags_replace(3, &[] as &[&str]),
+            Err(TagError::NoTags)
+        ));
+        assert_eq!(batch.tag_delete("").err(), Some(TagError::Empty));
+        assert_eq!(
+            batch.tag_rename("a,b", "").err(),
+            Some(TagError::Comma("a,b".to_string()))
+        );
+        assert_eq!(batch.len(), 3);
+    }
+
+    fn archive(item_id: u64) -> PocketSendAction {
+        PocketSendAction::Archive {
+            item_id,
+            time: None,
+        }
+    }
+
+    #[test]
+    fn test_outcomes_pair_actions_with_results() {
+        let actions = [archive(1), archive(2), archive(3)];
+        let response: PocketSendResponse = serde_json::from_str(
+            r#"{"status":1,"action_results":[true,false,false],"action_errors":[null,{"code":422,"message":"Invalid","type":"Unprocessable Entity"},null]}"#,
+        )
+        .unwrap();
+
+        let outcomes = response.outcomes(&actions).unwrap();
+
+        assert_eq!(
+            outcomes,
+            vec![
+                (&actions[0], ActionOutcome::Succeeded),
+                (
+                    &actions[1],
+                    ActionOutcome::Failed {
+                        code: 422,
+                        message: "Invalid".to_string(),
+                        error_type: "Unprocessable Entity".to_string(),
+                    }
+                ),
+                (
+                    &actions[2],
+                    ActionOutcome::Failed {
+                        code: 0,
+                        message: "Action failed".to_string(),
+                        error_type: String::new(),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ensure_success_reports_failed_actions() {
+        let actions = [archive(1), archive(2)];
+        let response: PocketSendResponse = serde_json::from_str(
+            r#"{"status":1,"action_results":[true,false],"action_errors":[null,{"code":422,"message":"Invalid","type":"Unprocessable Entity"}]}"#,
+        )
+        .unwrap();
+
+        match response.ensure_success(&actions) {
+            Err(PocketError::ActionsFailed { outcomes }) => {
+                assert_eq!(outcomes.len(), 2);
+                assert_eq!(outcomes[0], ActionOutcome::Succeeded);
+                assert!(matches!(
+                    outcomes[1],
+                    ActionOutcome::Failed { code: 422, .. }
+                ));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_outcomes_reports_result_count_mismatch() {
+        let response: PocketSendResponse =
+            serde_json::from_str(r#"{"status":1,"action_results":[true],"action_errors":[null]}"#)
+                .unwrap();
+
+        let (first, second) = (archive(1), archive(2));
+        let request = PocketSendRequest {
+            actions: &[&first, &second],
+        };
+        let result = response.outcomes(request.actions);
+
+        assert!(matches!(
+            result,
+            Err(PocketError::ResultCountMismatch {
+                expected: 2,
+                actual: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_tags_are_validated() {
+        assert_eq!(
+            Tags::new([" rust ", "async"]).unwrap().as_slice(),
+            ["rust", "async"]
+        );
+        assert_eq!(Tags::new(["rust", " "]), Err(TagError::Empty));
+        assert_eq!(Tags::new(["a,b"]), Err(TagError::Comma("a,b".to_string())));
+        assert_eq!(
+            "rust, async".parse::<Tags>().unwrap().to_string(),
+            "rust,async"
+        );
+        assert_eq!(Tags::new(Vec::<String>::new()), Err(TagError::NoTags));
+        assert_eq!("".parse::<Tags>(), Err(TagError::NoTags));
+        assert_eq!("rust,,async".parse::<Tags>(), Err(TagError::Empty));
+    }
+
+    #[test]
+    fn test_send_action_round_trip() {
+        let actions = vec![
+            PocketSendAction::TagsReplace {
+                item_id: 1,
+                tags: Tags::new(["one", "two"]).unwrap(),
+                time: Some(Utc.with_ymd_and_hms(2020, 3, 14, 21, 29, 13).unwrap()),
+            },
+            PocketSendAction::Add {
+                item_id: None,
+                ref_id: None,
+                tags: Some(Tags::new(["one"]).unwrap()),
+                time: None,
+                title: Some("Example".to_string()),
+                url: Url::parse("https://example.com").ok(),
+            },
+            PocketSendAction::TagRename {
+                old_tag: Tag::new("one").unwrap(),
+                new_tag: Tag::new("1").unwrap(),
+                time: None,
+            },
+        ];
+
+        let json = serde_json::to_value(&actions).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"action": "tags_replace", "item_id": "1", "tags": "one,two", "time": "1584221353"},
+                {"action": "add", "item_id": null, "ref_id": null, "tags": "one", "time": null, "title": "Example", "url": "https://example.com/"},
+                {"action": "tag_rename", "old_tag": "one", "new_tag": "1", "time": null},
+            ])
+        );
+        assert_eq!(
+            serde_json::from_value::<Vec<PocketSendAction>>(json).unwrap(),
+            actions
+        );
+        assert_eq!(
+            serde_json::from_str::<Vec<PocketSendAction>>(
+                r#"[{"action": "archive", "item_id": 1}, {"action": "add", "item_id": 2}]"#
+            )
+            .unwrap(),
+            vec![
+                archive(1),
+                PocketSendAction::Add {
+                    item_id: Some(2),
+                    ref_id: None,
+                    tags: None,
+                    time: None,
+                    title: None,
+                    url: None,
+                },
+            ]
+        );
+        assert!(serde_json::from_str::<PocketSendAction>(
+            r#"{"action": "tag_delete", "tag": " "}"#
+        )
+        .is_err());
+        assert!(serde_json::from_str::<PocketSendAction>(
+            r#"{"action": "tag_rename", "old_tag": "a,b", "new_tag": "c"}"#
+        )
+        .is_err());
+    }
 }
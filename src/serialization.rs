@@ -1,9 +1,10 @@
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use mime::Mime;
 use serde::de::{DeserializeOwned, Unexpected};
 use serde::{Deserialize, Deserializer, Serializer};
 use serde_json::Value;
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::fmt::Display;
 use std::result::Result;
 use std::str::FromStr;
@@ -30,6 +31,27 @@ where
     T::from_str(&s).map_err(serde::de::Error::custom)
 }
 
+/// Accepts a number given either as a JSON number or as a string.
+pub fn from_str_or_int<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr + TryFrom<u64>,
+    <T as FromStr>::Err: Display,
+    <T as TryFrom<u64>>::Error: Display,
+    D: Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::String(s) => T::from_str(&s).map_err(serde::de::Error::custom),
+        Value::Number(n) => n
+            .as_u64()
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid number {}", n)))
+            .and_then(|n| T::try_from(n).map_err(serde::de::Error::custom)),
+        other => Err(serde::de::Error::invalid_value(
+            Unexpected::Other(format!("{:?}", other).as_str()),
+            &"number or numeric string",
+        )),
+    }
+}
+
 pub fn optional_to_string<T, S>(x: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
 where
     T: ToString,
@@ -108,7 +130,7 @@ where
 }
 
 pub fn map_to_vec<T>(map: BTreeMap<String, T>) -> Vec<T> {
-    map.into_iter().map(|(_, v)| v).collect::<Vec<_>>()
+    map.into_values().collect::<Vec<_>>()
 }
 
 // https://github.com/serde-rs/serde/issues/1344
@@ -140,6 +162,56 @@ where
     }
 }
 
+/// Accepts a JSON/TOML bool as well as Pocket's `0`/`1` given as a number or a string.
+pub fn optional_bool_from_bool_or_int<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<Value>::deserialize(deserializer)? {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Bool(b)) => Ok(Some(b)),
+        Some(value) => match from_str_or_int::<u8, _>(value).map_err(serde::de::Error::custom)? {
+            0 => Ok(Some(false)),
+            1 => Ok(Some(true)),
+            other => Err(serde::de::Error::invalid_value(
+                Unexpected::Unsigned(other as u64),
+                &"zero or one",
+            )),
+        },
+    }
+}
+
+/// Like `from_str_or_int`, for optional fields.
+pub fn option_from_str_or_int<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: FromStr + TryFrom<u64>,
+    <T as FromStr>::Err: Display,
+    <T as TryFrom<u64>>::Error: Display,
+    D: Deserializer<'de>,
+{
+    match Option::<Value>::deserialize(deserializer)? {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => from_str_or_int(value)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// Accepts a unix timestamp given as a number or a string, `0` meaning none.
+pub fn option_timestamp_from_str_or_int<'de, D>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match option_from_str_or_int::<i64, D>(deserializer)? {
+        None | Some(0) => Ok(None),
+        Some(timestamp) => timestamp_to_datetime(timestamp)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
 #[allow(clippy::trivially_copy_pass_by_ref)]
 pub fn optional_bool_to_int<S>(x: &Option<bool>, serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -164,11 +236,13 @@ where
     }
 }
 
+pub const UNTAGGED: &str = "_untagged_";
+
 pub fn untagged_to_str<S>(serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    serializer.serialize_str("_untagged_")
+    serializer.serialize_str(UNTAGGED)
 }
 
 pub fn option_mime_from_string<'de, D>(deserializer: D) -> Result<Option<Mime>, D::Error>
@@ -191,7 +265,7 @@ where
     D: Deserializer<'de>,
 {
     let unix_timestamp = i64::deserialize(deserializer)?;
-    Ok(Utc.timestamp(unix_timestamp, 0))
+    timestamp_to_datetime(unix_timestamp).map_err(serde::de::Error::custom)
 }
 
 pub fn option_string_date_unix_timestamp_format<'de, D>(
@@ -204,11 +278,18 @@ where
         Some("0") | None => Ok(None),
         Some(str) => str
             .parse::<i64>()
-            .map(|i| Some(Utc.timestamp(i, 0)))
-            .map_err(serde::de::Error::custom),
+            .map_err(serde::de::Error::custom)
+            .and_then(|i| timestamp_to_datetime(i).map_err(serde::de::Error::custom))
+            .map(Some),
     })
 }
 
+pub fn timestamp_to_datetime(unix_timestamp: i64) -> Result<DateTime<Utc>, String> {
+    Utc.timestamp_opt(unix_timestamp, 0)
+        .single()
+        .ok_or_else(|| format!("timestamp out of range: {}", unix_timestamp))
+}
+
 pub const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
 pub fn option_string_date_format<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
@@ -217,16 +298,16 @@ where
 {
     match String::deserialize(deserializer)?.as_str() {
         "0000-00-00 00:00:00" => Ok(None),
-        str => Utc
-            .datetime_from_str(str, FORMAT)
+        str => NaiveDateTime::parse_from_str(str, FORMAT)
             .map_err(serde::de::Error::custom)
-            .map(Option::Some),
+            .map(|date| Some(date.and_utc())),
     }
 }
 
 // inspired by https://serde.rs/custom-date-format.html
 pub mod string_date_unix_timestamp_format {
-    use chrono::{DateTime, TimeZone, Utc};
+    use super::timestamp_to_datetime;
+    use chrono::{DateTime, Utc};
     use serde::{self, Deserialize, Deserializer, Serializer};
 
     pub fn serialize<S>(date: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
@@ -242,8 +323,8 @@ pub mod string_date_unix_timestamp_format {
     {
         let s = String::deserialize(deserializer)?;
         s.parse::<i64>()
-            .map(|i| Utc.timestamp(i, 0))
             .map_err(serde::de::Error::custom)
+            .and_then(|i| timestamp_to_datetime(i).map_err(serde::de::Error::custom))
     }
 }
 
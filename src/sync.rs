@@ -0,0 +1,173 @@
+use crate::get::{PocketGetResponse, PocketItem, PocketItemStatus};
+use crate::serialization::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// Where the last `Pocket::sync` left off. Persist it between runs to only fetch
+/// what changed since then.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Clone)]
+pub struct SyncState {
+    /// Server time of the last successful sync, `None` before the first one.
+    #[serde(
+        default,
+        serialize_with = "optional_datetime_to_int",
+        deserialize_with = "option_timestamp_from_str_or_int"
+    )]
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl SyncState {
+    pub fn new() -> SyncState {
+        Default::default()
+    }
+}
+
+/// Items that changed since the previous sync.
+#[derive(Default, Debug, PartialEq, Clone)]
+pub struct SyncDelta {
+    /// Unread items added since the previous sync, or all unread items on the first one.
+    pub added: Vec<PocketItem>,
+    /// Unread items added earlier and modified since.
+    pub updated: Vec<PocketItem>,
+    pub archived: Vec<PocketItem>,
+    /// Ids of deleted items.
+    pub deleted: Vec<u64>,
+}
+
+impl SyncDelta {
+    pub(crate) fn new(entries: Vec<SyncEntry>, since: Option<DateTime<Utc>>) -> SyncDelta {
+        let mut delta = SyncDelta::default();
+        for entry in entries {
+            match entry {
+                SyncEntry::Deleted(item_id) => delta.deleted.push(item_id),
+                SyncEntry::Item(item) => match item.status {
+                    PocketItemStatus::Deleted => delta.deleted.push(item.item_id),
+                    PocketItemStatus::Archived => delta.archived.push(*item),
+                    PocketItemStatus::Normal
+                        if since.is_some_and(|since| item.time_added < since) =>
+                    {
+                        delta.updated.push(*item)
+                    }
+                    PocketItemStatus::Normal => delta.added.push(*item),
+                },
+            }
+        }
+        delta
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.updated.is_empty()
+            && self.archived.is_empty()
+            && self.deleted.is_empty()
+    }
+}
+
+pub(crate) type SyncResponse = PocketGetResponse<Vec<SyncEntry>>;
+
+/// Deleted items come back with little more than their id, so they are
+/// told apart before decoding the full item.
+#[derive(Debug, Clone)]
+pub(crate) enum SyncEntry {
+    Item(Box<PocketItem>),
+    Deleted(u64),
+}
+
+impl<'de> Deserialize<'de> for SyncEntry {
+    fn deserialize<D>(deserializer: D) -> Result<SyncEntry, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        if value.get("status").and_then(Value::as_str) == Some("2") {
+            value
+                .get("item_id")
+                .and_then(Value::as_str)
+                .and_then(|item_id| item_id.parse().ok())
+                .map(SyncEntry::Deleted)
+                .ok_or_else(|| serde::de::Error::missing_field("item_id"))
+        } else {
+            serde_json::from_value(value)
+                .map(|item| SyncEntry::Item(Box::new(item)))
+                .map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::test_item::TestItem;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn item(item_id: u64, status: &str, time_added: i64) -> TestItem {
+        TestItem::new(item_id).with(json!({
+            "status": status,
+            "time_added": time_added.to_string(),
+            "time_updated": time_added.to_string(),
+        }))
+    }
+
+    #[test]
+    fn test_sync_state_round_trip() {
+        let state = SyncState {
+            since: Some(Utc.timestamp_opt(1584221353, 0).unwrap()),
+        };
+
+        let json = serde_json::to_string(&state).unwrap();
+
+        assert_eq!(json, r#"{"since":"1584221353"}"#);
+        assert_eq!(serde_json::from_str::<SyncState>(&json).unwrap(), state);
+        assert_eq!(
+            serde_json::from_str::<SyncState>("{}").unwrap(),
+            SyncState::new()
+        );
+        assert_eq!(
+            serde_json::from_str::<SyncState>(r#"{"since":1584221353}"#).unwrap(),
+            state
+        );
+        assert_eq!(
+            toml::from_str::<SyncState>("since = 1584221353").unwrap(),
+            state
+        );
+    }
+
+    #[test]
+    fn test_sync_delta_classifies_items() {
+        let response = format!(
+            r#"{{"status":1,"complete":0,"list":{{"1":{},"2":{},"3":{},"4":{{"item_id":"4","status":"2"}}}},"error":null,"search_meta":{{"search_type":"normal"}},"since":1584221400}}"#,
+            item(1, "0", 1584221380),
+            item(2, "0", 1584221000),
+            item(3, "1", 1584221000),
+        );
+        let response: SyncResponse = serde_json::from_str(&response).unwrap();
+
+        let delta = SyncDelta::new(
+            response.list,
+            Some(Utc.timestamp_opt(1584221353, 0).unwrap()),
+        );
+
+        let ids = |items: &[PocketItem]| items.iter().map(|i| i.item_id).collect::<Vec<_>>();
+        assert_eq!(ids(&delta.added), vec![1]);
+        assert_eq!(ids(&delta.updated), vec![2]);
+        assert_eq!(ids(&delta.archived), vec![3]);
+        assert_eq!(delta.deleted, vec![4]);
+        assert_eq!(response.since.timestamp(), 1584221400);
+    }
+
+    #[test]
+    fn test_first_sync_treats_unread_items_as_added() {
+        let response = format!(
+            r#"{{"status":1,"complete":1,"list":[{}],"error":null,"search_meta":{{"search_type":"normal"}},"since":1584221400}}"#,
+            item(1, "0", 1584221000)
+        );
+        let response: SyncResponse = serde_json::from_str(&response).unwrap();
+
+        let delta = SyncDelta::new(response.list, None);
+
+        assert_eq!(delta.added.len(), 1);
+        assert!(delta.updated.is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+//! Boolean expressions over tags, e.g. `rust AND (async OR tokio) AND NOT read-later`,
+//! resolved by `Pocket::get_tagged` with as few single-tag `get` calls as possible.
+
+use crate::get::{PocketGetTag, PocketItem};
+use crate::query::{QueryErrorKind, QueryParseError};
+use crate::serialization::UNTAGGED;
+use std::ops::Not;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TagQuery {
+    Tag(String),
+    /// Items without any tag, written `_untagged_`.
+    Untagged,
+    And(Vec<TagQuery>),
+    Or(Vec<TagQuery>),
+    Not(Box<TagQuery>),
+}
+
+impl TagQuery {
+    pub fn tag<S: Into<String>>(tag: S) -> TagQuery {
+        TagQuery::Tag(tag.into())
+    }
+
+    /// Parses an expression of tags, `AND`, `OR`, `NOT` and parentheses.
+    /// `AND` binds tighter than `OR`; tags containing spaces can be `"quoted"`.
+    pub fn parse(input: &str) -> Result<TagQuery, QueryParseError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+            end: input.chars().count() + 1,
+        };
+
+        let query = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            Some(token) => Err(token.unexpected()),
+            None => Ok(query),
+        }
+    }
+
+    pub fn and(self, other: TagQuery) -> TagQuery {
+        match self {
+            TagQuery::And(mut queries) => {
+                queries.push(other);
+                TagQuery::And(queries)
+            }
+            query => TagQuery::And(vec![query, other]),
+        }
+    }
+
+    pub fn or(self, other: TagQuery) -> TagQuery {
+        match self {
+            TagQuery::Or(mut queries) => {
+                queries.push(other);
+                TagQuery::Or(queries)
+            }
+            query => TagQuery::Or(vec![query, other]),
+        }
+    }
+
+    /// Evaluates the expression against the item's `tags`, which are only
+    /// returned for `detailType=complete` requests.
+    pub fn matches(&self, item: &PocketItem) -> bool {
+        let tags = item
+            .tags
+            .iter()
+            .flatten()
+            .map(|tag| tag.tag.as_str())
+            .collect::<Vec<_>>();
+        self.eval(&tags)
+    }
+
+    fn eval(&self, tags: &[&str]) -> bool {
+        match self {
+            TagQuery::Tag(tag) => tags.contains(&tag.as_str()),
+            TagQuery::Untagged => tags.is_empty(),
+            TagQuery::And(queries) => queries.iter().all(|q| q.eval(tags)),
+            TagQuery::Or(queries) => queries.iter().any(|q| q.eval(tags)),
+            TagQuery::Not(query) => !query.eval(tags),
+        }
+    }
+
+    pub(crate) fn is_single_tag(&self) -> bool {
+        matches!(self, TagQuery::Tag(_) | TagQuery::Untagged)
+    }
+
+    /// The single-tag filters whose results together contain every matching item,
+    /// or `None` if the expression can match items that have none of its tags.
+    pub(crate) fn plan(&self) -> Option<Vec<PocketGetTag<'static>>> {
+        match self {
+            TagQuery::Tag(tag) => Some(vec![PocketGetTag::tagged(tag.clone())]),
+            TagQuery::Untagged => Some(vec![PocketGetTag::Untagged]),
+            TagQuery::And(queries) => queries
+                .iter()
+                .filter_map(TagQuery::plan)
+                .min_by_key(Vec::len),
+            TagQuery::Or(queries) => {
+                let mut tags = vec![];
+                for query in queries {
+                    for tag in query.plan()? {
+                        if !tags.contains(&tag) {
+                            tags.push(tag);
+                        }
+                    }
+                }
+                Some(tags)
+            }
+            TagQuery::Not(_) => None,
+        }
+    }
+}
+
+impl Not for TagQuery {
+    type Output = TagQuery;
+
+    fn not(self) -> TagQuery {
+        match self {
+            TagQuery::Not(query) => *query,
+            query => TagQuery::Not(Box::new(query)),
+        }
+    }
+}
+
+impl From<PocketGetTag<'_>> for TagQuery {
+    fn from(tag: PocketGetTag<'_>) -> TagQuery {
+        match tag {
+            PocketGetTag::Untagged => TagQuery::Untagged,
+            PocketGetTag::Tagged(tag) => TagQuery::Tag(tag.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum TokenKind {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Tag(String),
+}
+
+struct Token {
+    column: usize,
+    text: String,
+    kind: TokenKind,
+}
+
+impl Token {
+    fn unexpected(&self) -> QueryParseError {
+        QueryParseError {
+            column: self.column,
+            kind: QueryErrorKind::UnexpectedToken {
+                token: self.text.clone(),
+            },
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryParseError> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let start = pos;
+        let kind = match chars[pos] {
+            c if c.is_whitespace() => {
+                pos += 1;
+                continue;
+            }
+            '(' => {
+                pos += 1;
+                TokenKind::Open
+            }
+            ')' => {
+                pos += 1;
+                TokenKind::Close
+            }
+            '"' => {
+                let len =
+                    chars[pos + 1..]
+                        .iter()
+                        .position(|&c| c == '"')
+                        .ok_or(QueryParseError {
+                            column: pos + 1,
+                            kind: QueryErrorKind::UnterminatedQuote,
+                        })?;
+                pos += len + 2;
+                TokenKind::Tag(chars[start + 1..pos - 1].iter().collect())
+            }
+            _ => {
+                while pos < chars.len() && is_word_char(chars[pos]) {
+                    pos += 1;
+                }
+                match chars[start..pos].iter().collect::<String>() {
+                    word if word == "AND" => TokenKind::And,
+                    word if word == "OR" => TokenKind::Or,
+                    word if word == "NOT" => TokenKind::Not,
+                    word => TokenKind::Tag(word),
+                }
+            }
+        };
+
+        tokens.push(Token {
+            column: start + 1,
+            text: chars[start..pos].iter().collect(),
+            kind,
+        });
+    }
+
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && c != '(' && c != ')'
+}
+
+struct Parser<'t> {
+    tokens: &'t [Token],
+    pos: usize,
+    /// Column reported for a missing token at the end of the input.
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Result<&Token, QueryParseError> {
+        let token = self.tokens.get(self.pos).ok_or(QueryParseError {
+            column: self.end,
+            kind: QueryErrorKind::UnexpectedEnd,
+        })?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn next_is(&mut self, kind: TokenKind) -> bool {
+        let is = self.tokens.get(self.pos).is_some_and(|t| t.kind == kind);
+        if is {
+            self.pos += 1;
+        }
+        is
+    }
+
+    fn parse_or(&mut self) -> Result<TagQuery, QueryParseError> {
+        let mut query = self.parse_and()?;
+        while self.next_is(TokenKind::Or) {
+            query = query.or(self.parse_and()?);
+        }
+        Ok(query)
+    }
+
+    fn parse_and(&mut self) -> Result<TagQuery, QueryParseError> {
+        let mut query = self.parse_unary()?;
+        while self.next_is(TokenKind::And) {
+            query = query.and(self.parse_unary()?);
+        }
+        Ok(query)
+    }
+
+    fn parse_unary(&mut self) -> Result<TagQuery, QueryParseError> {
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::Not => Ok(!self.parse_unary()?),
+            TokenKind::Open => {
+                let query = self.parse_or()?;
+                let close = self.next()?;
+                match close.kind {
+                    TokenKind::Close => Ok(query),
+                    _ => Err(close.unexpected()),
+                }
+            }
+            TokenKind::Tag(ref tag) if tag == UNTAGGED => Ok(TagQuery::Untagged),
+            TokenKind::Tag(ref tag) => Ok(TagQuery::tag(tag.as_str())),
+            _ => Err(token.unexpected()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_nested_query() {
+        let query = TagQuery::parse(r#"rust AND (async OR tokio) AND NOT "read later""#).unwrap();
+
+        assert_eq!(
+            query,
+            TagQuery::tag("rust")
+                .and(TagQuery::tag("async").or(TagQuery::tag("tokio")))
+                .and(!TagQuery::tag("read later"))
+        );
+    }
+
+    #[test]
+    fn test_and_binds_tighter_than_or() {
+        assert_eq!(
+            TagQuery::parse("a OR b AND _untagged_").unwrap(),
+            TagQuery::tag("a").or(TagQuery::tag("b").and(TagQuery::Untagged))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = TagQuery::parse("rust AND (async").unwrap_err();
+        assert_eq!(
+            (error.column, error.kind),
+            (16, QueryErrorKind::UnexpectedEnd)
+        );
+
+        let error = TagQuery::parse("rust tokio").unwrap_err();
+        assert_eq!(error.column, 6);
+        assert_eq!(error.to_string(), "unexpected \"tokio\" at column 6");
+
+        assert_eq!(TagQuery::parse("OR rust").unwrap_err().column, 1);
+    }
+
+    #[test]
+    fn test_eval() {
+        let query = TagQuery::parse("rust AND (async OR tokio) AND NOT read-later").unwrap();
+
+        assert!(query.eval(&["rust", "tokio"]));
+        assert!(!query.eval(&["rust"]));
+        assert!(!query.eval(&["rust", "async", "read-later"]));
+        assert!(TagQuery::Untagged.eval(&[]));
+    }
+
+    #[test]
+    fn test_plan_uses_fewest_calls() {
+        let tagged = |tag: &str| PocketGetTag::tagged(tag.to_string());
+
+        let query = TagQuery::parse("(async OR tokio) AND rust AND NOT read-later").unwrap();
+        assert_eq!(query.plan(), Some(vec![tagged("rust")]));
+
+        let query = TagQuery::parse("async OR tokio OR async").unwrap();
+        assert_eq!(query.plan(), Some(vec![tagged("async"), tagged("tokio")]));
+
+        assert_eq!(TagQuery::parse("NOT rust").unwrap().plan(), None);
+        assert_eq!(TagQuery::parse("rust OR NOT async").unwrap().plan(), None);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::errors::{PocketError, TimeoutKind};
+use crate::PocketResult;
+use futures::future::BoxFuture;
+use futures::TryFutureExt;
+use hyper::client::{Client, HttpConnector};
+use hyper::{Body, Request, Response};
+use hyper_tls::HttpsConnector;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Sends HTTP requests on behalf of `Pocket` and `PocketAuthentication`.
+///
+/// Implement this to plug in another HTTP stack, add middleware or stub the network
+/// in tests. Errors that are not `hyper` errors can be reported as
+/// `PocketError::Transport`.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: Request<Body>) -> BoxFuture<'static, PocketResult<Response<Body>>>;
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn send(&self, request: Request<Body>) -> BoxFuture<'static, PocketResult<Response<Body>>> {
+        (**self).send(request)
+    }
+}
+
+/// The default transport, a `hyper` client supporting both HTTPS and plain HTTP.
+pub struct HyperTransport {
+    client: Client<HttpsConnector<HttpConnector>>,
+}
+
+impl HyperTransport {
+    pub fn new() -> HyperTransport {
+        HyperTransport::with_connect_timeout(None)
+    }
+
+    pub fn with_connect_timeout(connect_timeout: Option<Duration>) -> HyperTransport {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        http.set_connect_timeout(connect_timeout);
+        let https = HttpsConnector::new_with_connector(http);
+        let client = Client::builder().build::<_, hyper::Body>(https);
+
+        HyperTransport { client }
+    }
+}
+
+impl Default for HyperTransport {
+    fn default() -> HyperTransport {
+        HyperTransport::new()
+    }
+}
+
+impl Transport for HyperTransport {
+    fn send(&self, request: Request<Body>) -> BoxFuture<'static, PocketResult<Response<Body>>> {
+        Box::pin(self.client.request(request).map_err(from_hyper_error))
+    }
+}
+
+fn from_hyper_error(error: hyper::Error) -> PocketError {
+    let mut source = std::error::Error::source(&error);
+    while let Some(cause) = source {
+        if let Some(io) = cause.downcast_ref::<io::Error>() {
+            if error.is_connect() && io.kind() == io::ErrorKind::TimedOut {
+                return PocketError::Timeout(TimeoutKind::Connect);
+            }
+        }
+        source = cause.source();
+    }
+    PocketError::Http(error)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::{TcpListener, TcpStream};
+
+    #[tokio::test]
+    async fn test_connect_timeout() {
+        // Fill the accept queue of a listener that never accepts, so that further
+        // connection attempts hang until they time out.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut queued = vec![];
+        while let Ok(stream) = TcpStream::connect_timeout(&addr, Duration::from_millis(100)) {
+            queued.push(stream);
+        }
+
+        let transport = HyperTransport::with_connect_timeout(Some(Duration::from_millis(50)));
+        let request = Request::get(format!("http://{}/", addr))
+            .body(Body::empty())
+            .unwrap();
+        let result = transport.send(request).await;
+
+        assert!(matches!(
+            result,
+            Err(PocketError::Timeout(TimeoutKind::Connect))
+        ));
+    }
+}
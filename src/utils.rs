@@ -1,4 +1,207 @@
+use url::Url;
+
+pub fn with_trailing_slash(mut url: Url) -> Url {
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    url
+}
+
 #[cfg(test)]
 pub fn remove_whitespace(s: &str) -> String {
     s.replace(|c: char| c.is_whitespace(), "")
 }
+
+#[cfg(test)]
+pub mod test_server {
+    use hyper::header::HeaderValue;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Method, Request, Response, Server, StatusCode};
+    use std::convert::Infallible;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use url::Url;
+
+    #[derive(Debug, Clone)]
+    pub struct RecordedRequest {
+        pub method: Method,
+        pub uri: String,
+        pub body: String,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct TestResponse {
+        pub status: StatusCode,
+        pub headers: Vec<(&'static str, Vec<u8>)>,
+        pub body: String,
+        pub delay: Option<Duration>,
+    }
+
+    impl TestResponse {
+        pub fn json(body: &str) -> TestResponse {
+            TestResponse {
+                status: StatusCode::OK,
+                headers: vec![("Content-Type", b"application/json".to_vec())],
+                body: body.to_string(),
+                delay: None,
+            }
+        }
+
+        pub fn delay(mut self, delay: Duration) -> TestResponse {
+            self.delay = Some(delay);
+            self
+        }
+
+        pub fn status(mut self, status: u16) -> TestResponse {
+            self.status = StatusCode::from_u16(status).unwrap();
+            self
+        }
+
+        pub fn header(self, name: &'static str, value: &str) -> TestResponse {
+            self.raw_header(name, value.as_bytes())
+        }
+
+        pub fn raw_header(mut self, name: &'static str, value: &[u8]) -> TestResponse {
+            self.headers.push((name, value.to_vec()));
+            self
+        }
+    }
+
+    struct State {
+        responses: Vec<TestResponse>,
+        requests: Vec<RecordedRequest>,
+    }
+
+    /// A local HTTP server answering with `responses` in order, repeating the last one.
+    pub struct TestServer {
+        pub url: Url,
+        state: Arc<Mutex<State>>,
+    }
+
+    impl TestServer {
+        pub fn start(responses: Vec<TestResponse>) -> TestServer {
+            assert!(!responses.is_empty(), "test server needs a response");
+            let state = Arc::new(Mutex::new(State {
+                responses,
+                requests: vec![],
+            }));
+
+            let service_state = state.clone();
+            let make_service = make_service_fn(move |_| {
+                let state = service_state.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |request| {
+                        TestServer::respond(state.clone(), request)
+                    }))
+                }
+            });
+            let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+            let url = Url::parse(&format!("http://{}/v3/", server.local_addr())).unwrap();
+            tokio::spawn(server);
+
+            TestServer { url, state }
+        }
+
+        pub fn requests(&self) -> Vec<RecordedRequest> {
+            self.state.lock().unwrap().requests.clone()
+        }
+
+        async fn respond(
+            state: Arc<Mutex<State>>,
+            request: Request<Body>,
+        ) -> Result<Response<Body>, Infallible> {
+            let (parts, body) = request.into_parts();
+            let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+            let response = {
+                let mut state = state.lock().unwrap();
+                let index = state.requests.len().min(state.responses.len() - 1);
+                state.requests.push(RecordedRequest {
+                    method: parts.method,
+                    uri: parts.uri.to_string(),
+                    body: String::from_utf8_lossy(&body).into_owned(),
+                });
+                state.responses[index].clone()
+            };
+
+            if let Some(delay) = response.delay {
+                tokio::time::delay_for(delay).await;
+            }
+
+            let mut builder = Response::builder().status(response.status);
+            for (name, value) in response.headers {
+                builder = builder.header(name, HeaderValue::from_bytes(&value).unwrap());
+            }
+            Ok(builder.body(Body::from(response.body)).unwrap())
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_item {
+    use crate::get::PocketItem;
+    use serde_json::{json, Value};
+    use std::fmt;
+
+    /// The JSON of a minimal `/v3/get` item, encoded the way Pocket sends it.
+    #[derive(Debug, Clone)]
+    pub struct TestItem(Value);
+
+    impl TestItem {
+        pub fn new(item_id: u64) -> TestItem {
+            TestItem(json!({
+                "item_id": item_id.to_string(),
+                "given_title": "",
+                "word_count": "0",
+                "excerpt": "",
+                "time_added": "1584221353",
+                "time_read": "0",
+                "time_updated": "1584221353",
+                "time_favorited": "0",
+                "favorite": "0",
+                "is_index": "0",
+                "is_article": "1",
+                "has_image": "0",
+                "has_video": "0",
+                "resolved_id": item_id.to_string(),
+                "resolved_title": "",
+                "sort_id": 0,
+                "status": "0",
+                "lang": "en",
+            }))
+        }
+
+        /// Sets (or overrides) every field of the `fields` object.
+        pub fn with(mut self, fields: Value) -> TestItem {
+            for (key, value) in fields.as_object().expect("fields must be an object") {
+                self.0[key] = value.clone();
+            }
+            self
+        }
+
+        pub fn without(mut self, key: &str) -> TestItem {
+            self.0.as_object_mut().unwrap().remove(key);
+            self
+        }
+
+        /// Sets `tags` to Pocket's map of tag name to tag.
+        pub fn tags(self, tags: &[&str]) -> TestItem {
+            let item_id = self.0["item_id"].clone();
+            let tags = tags
+                .iter()
+                .map(|&tag| (tag.to_string(), json!({"item_id": item_id, "tag": tag})))
+                .collect::<serde_json::Map<_, _>>();
+            self.with(json!({ "tags": tags }))
+        }
+
+        pub fn build(self) -> PocketItem {
+            serde_json::from_value(self.0).unwrap()
+        }
+    }
+
+    impl fmt::Display for TestItem {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+}